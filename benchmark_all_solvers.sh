@@ -1,4 +1,4 @@
-for s in "first-uip-basic" "first-uip" "second-uip" "third-uip" "all-uip" "saturating-all-uip" "rel-sat" "min-cut"
+for s in "first-uip-basic" "first-uip" "second-uip" "third-uip" "all-uip" "saturating-all-uip" "kth-uip" "trail-kth-uip" "trail-all-uip" "rel-sat" "min-cut" "lbd" "activity" "hybrid"
 do
 	echo "Running: $s"
 	./benchmark_solver.sh "$s"
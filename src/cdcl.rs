@@ -1,14 +1,24 @@
 pub mod decision;
+pub mod dynamic;
+pub mod enumerate;
 pub mod first_uip;
+pub mod hybrid;
+pub mod implication_graph;
 pub mod mincut;
 pub mod propagation;
+pub mod trace;
+pub mod uip;
 
+use crate::cdcl::dynamic::{ConflictAnalysisFactory, DecisionHeuristicFactory};
 use crate::cdcl::propagation::{ConflictAnalysis, DecisionHeuristic};
 #[allow(unused_imports)]
-pub use propagation::{variable_name, Clause, Formula, Literal, Variable, CDCL};
+pub use propagation::{
+    variable_name, Clause, Formula, Literal, Progress, ProgressEvent, SolveResult, SolverOptions,
+    SolverStatistics, Variable, CDCL,
+};
 use std::io::BufRead;
 
-pub fn is_satisfying(formula: &Formula, assignment: &Vec<bool>) -> bool {
+pub fn is_satisfying(formula: &Formula, assignment: &[bool]) -> bool {
     formula.iter().all(|clause| {
         clause
             .iter()
@@ -18,6 +28,49 @@ pub fn is_satisfying(formula: &Formula, assignment: &Vec<bool>) -> bool {
 }
 
 pub fn read_dimacs<R: BufRead>(reader: &mut R) -> (usize, Formula) {
+    let (n, formula, _) = read_dimacs_with_projection(reader);
+
+    (n, formula)
+}
+
+/// Also returns the variables of the `c ind` lines, the projection of model counting and
+/// enumeration, or `None` if there are none.
+///
+/// # Panics
+///
+/// Panics on malformed input, including `c ind` variables that do not parse or lie outside
+/// `1..=n`.
+pub fn read_dimacs_with_projection<R: BufRead>(
+    reader: &mut R,
+) -> (usize, Formula, Option<Vec<Variable>>) {
+    let mut projection: Option<Vec<Variable>> = None;
+
+    let mut read_comment = |line: &str| {
+        let mut tokens = line.split_whitespace();
+
+        if tokens.next() == Some("c") && tokens.next() == Some("ind") {
+            let variables = projection.get_or_insert_with(Vec::new);
+
+            while let Some(s) = tokens.next() {
+                let variable = s
+                    .parse::<Variable>()
+                    .unwrap_or_else(|_| panic!("Invalid variable '{s}' in a 'c ind' line"));
+
+                if variable == 0 {
+                    assert_eq!(
+                        tokens.next(),
+                        None,
+                        "Variables after the 0 of a 'c ind' line"
+                    );
+
+                    break;
+                }
+
+                variables.push(variable - 1);
+            }
+        }
+    };
+
     let [n, m] = loop {
         let mut input = String::new();
 
@@ -38,6 +91,8 @@ pub fn read_dimacs<R: BufRead>(reader: &mut R) -> (usize, Formula) {
             break tmp;
         } else {
             assert!(line.starts_with("c"));
+
+            read_comment(line);
         }
     };
 
@@ -49,7 +104,7 @@ pub fn read_dimacs<R: BufRead>(reader: &mut R) -> (usize, Formula) {
         let line = input.trim();
 
         if line.starts_with("c") {
-            continue;
+            read_comment(line);
         } else {
             let mut clause: Vec<_> = line
                 .split(" ")
@@ -70,17 +125,66 @@ pub fn read_dimacs<R: BufRead>(reader: &mut R) -> (usize, Formula) {
         input.clear();
     }
 
-    (n, ans)
+    if let Some(variable) = projection.iter().flatten().find(|&&variable| variable >= n) {
+        panic!(
+            "Variable {} of a 'c ind' line exceeds the {n} variables of the formula",
+            variable + 1
+        );
+    }
+
+    (n, ans, projection)
 }
 
 pub fn cdcl_solve<D: DecisionHeuristic, C: ConflictAnalysis>(
     n: usize,
     formula: &mut Formula,
 ) -> Option<Vec<bool>> {
-    let d = D::from_formula(n, &formula);
-    let c = C::from_formula(n, &formula);
+    cdcl_solve_with::<D, C>(n, formula, SolverOptions::default())
+}
+
+pub fn cdcl_solve_with<D: DecisionHeuristic, C: ConflictAnalysis>(
+    n: usize,
+    formula: &mut Formula,
+    options: SolverOptions,
+) -> Option<Vec<bool>> {
+    let d = D::from_formula(n, formula);
+    let c = C::from_formula(n, formula);
+
+    let mut cdcl = CDCL::new(n, formula, d, c).with_options(options);
+
+    if cdcl.solve() {
+        Some(cdcl.get_assignment())
+    } else {
+        None
+    }
+}
+
+pub fn cdcl_solve_dyn(
+    n: usize,
+    formula: &mut Formula,
+    decision_heuristic: &dyn DecisionHeuristicFactory,
+    conflict_analysis: &dyn ConflictAnalysisFactory,
+) -> Option<Vec<bool>> {
+    cdcl_solve_dyn_with(
+        n,
+        formula,
+        decision_heuristic,
+        conflict_analysis,
+        SolverOptions::default(),
+    )
+}
+
+pub fn cdcl_solve_dyn_with(
+    n: usize,
+    formula: &mut Formula,
+    decision_heuristic: &dyn DecisionHeuristicFactory,
+    conflict_analysis: &dyn ConflictAnalysisFactory,
+    options: SolverOptions,
+) -> Option<Vec<bool>> {
+    let d = decision_heuristic.create(n, formula);
+    let c = conflict_analysis.create(n, formula);
 
-    let mut cdcl = CDCL::new(n, formula, d, c);
+    let mut cdcl = CDCL::new(n, formula, d, c).with_options(options);
 
     if cdcl.solve() {
         Some(cdcl.get_assignment())
@@ -39,4 +39,8 @@ impl DecisionHeuristic for DecideFirstVariable {
                 }
             })
     }
+
+    fn decides_before(&self, a: Variable, b: Variable) -> bool {
+        a < b
+    }
 }
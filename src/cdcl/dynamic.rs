@@ -0,0 +1,39 @@
+use crate::cdcl::propagation::*;
+
+pub trait DecisionHeuristicFactory {
+    fn create(&self, n: usize, formula: &Formula) -> Box<dyn DynDecisionHeuristic>;
+}
+
+pub trait ConflictAnalysisFactory {
+    fn create(&self, n: usize, formula: &Formula) -> Box<dyn DynConflictAnalysis>;
+}
+
+impl<F: Fn(usize, &Formula) -> Box<dyn DynDecisionHeuristic>> DecisionHeuristicFactory for F {
+    fn create(&self, n: usize, formula: &Formula) -> Box<dyn DynDecisionHeuristic> {
+        self(n, formula)
+    }
+}
+
+impl<F: Fn(usize, &Formula) -> Box<dyn DynConflictAnalysis>> ConflictAnalysisFactory for F {
+    fn create(&self, n: usize, formula: &Formula) -> Box<dyn DynConflictAnalysis> {
+        self(n, formula)
+    }
+}
+
+pub fn decision_heuristic_factory<D: DecisionHeuristic + 'static>(
+) -> Box<dyn DecisionHeuristicFactory> {
+    Box::new(
+        |n: usize, formula: &Formula| -> Box<dyn DynDecisionHeuristic> {
+            Box::new(D::from_formula(n, formula))
+        },
+    )
+}
+
+pub fn conflict_analysis_factory<C: ConflictAnalysis + 'static>() -> Box<dyn ConflictAnalysisFactory>
+{
+    Box::new(
+        |n: usize, formula: &Formula| -> Box<dyn DynConflictAnalysis> {
+            Box::new(C::from_formula(n, formula))
+        },
+    )
+}
@@ -0,0 +1,173 @@
+use crate::cdcl::propagation::{DynConflictAnalysis, DynDecisionHeuristic, SolveResult, Trail};
+use crate::cdcl::{variable_name, Clause, Formula, Literal, Variable, CDCL};
+
+/// How a model is excluded once found.
+#[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum Blocking {
+    /// The negation of the model, on the projection if there is one.
+    #[default]
+    Model,
+    /// The negation of the decisions the model, or its projection, follows from, which is
+    /// usually shorter. It only excludes every model with the same projection if these
+    /// decisions are all on projected variables, as when the decision heuristic decides them
+    /// first; otherwise the model is blocked.
+    Decisions,
+}
+
+/// Enumerates the models of the formula of a solver, optionally projected onto a set of
+/// variables: every model found is blocked by a clause added to the running solver, which goes
+/// on from the backjump instead of starting over. Models with the same projection are only
+/// reported once.
+pub struct ModelEnumerator<'a, D: DynDecisionHeuristic, C: DynConflictAnalysis> {
+    cdcl: CDCL<'a, D, C>,
+    /// Sorted, without duplicates.
+    projection: Option<Vec<Variable>>,
+    blocking: Blocking,
+    result: Option<SolveResult>,
+    models: usize,
+}
+
+impl<'a, D: DynDecisionHeuristic, C: DynConflictAnalysis> ModelEnumerator<'a, D, C> {
+    pub fn new(cdcl: CDCL<'a, D, C>) -> Self {
+        Self {
+            cdcl,
+            projection: None,
+            blocking: Blocking::Model,
+            result: None,
+            models: 0,
+        }
+    }
+
+    /// Only enumerate the distinct assignments of `projection`.
+    pub fn with_projection(mut self, mut projection: Vec<Variable>) -> Self {
+        projection.sort_unstable();
+        projection.dedup();
+
+        self.projection = Some(projection);
+        self
+    }
+
+    pub fn with_blocking(mut self, blocking: Blocking) -> Self {
+        self.blocking = blocking;
+        self
+    }
+
+    /// The next model, a full assignment whose projection was not reported yet. `None` once
+    /// the search ended, see [`result`](Self::result).
+    pub fn next_model(&mut self) -> Option<Vec<bool>> {
+        if self.result.is_some() {
+            return None;
+        }
+
+        match self.cdcl.search() {
+            SolveResult::Satisfiable => {
+                let model = self.cdcl.get_assignment();
+                let clause = self.blocking_clause();
+
+                self.models += 1;
+
+                if !self.cdcl.add_blocking_clause(clause) {
+                    self.result = Some(SolveResult::Unsatisfiable);
+                }
+
+                Some(model)
+            }
+            result => {
+                self.result = Some(result);
+
+                None
+            }
+        }
+    }
+
+    /// How the search ended: [`SolveResult::Unsatisfiable`] once every model was reported,
+    /// `None` while it goes on.
+    pub fn result(&self) -> Option<SolveResult> {
+        self.result
+    }
+
+    /// Number of models reported.
+    pub fn models(&self) -> usize {
+        self.models
+    }
+
+    pub fn cdcl(&self) -> &CDCL<'a, D, C> {
+        &self.cdcl
+    }
+
+    fn blocking_clause(&self) -> Clause {
+        let trail = self.cdcl.trail();
+        let variables = match &self.projection {
+            Some(projection) => projection.clone(),
+            None => (0..trail.assignment.len()).collect(),
+        };
+
+        let model = || {
+            variables
+                .iter()
+                .map(|&variable| !trail.to_literal(variable))
+                .collect()
+        };
+
+        match self.blocking {
+            Blocking::Model => model(),
+            Blocking::Decisions => {
+                let decisions = implying_decisions(self.cdcl.formula(), trail, &variables);
+
+                let projected = |literal: &Literal| {
+                    self.projection.as_ref().is_none_or(|projection| {
+                        projection.binary_search(&variable_name(*literal)).is_ok()
+                    })
+                };
+
+                if decisions.iter().all(projected) {
+                    decisions
+                } else {
+                    model()
+                }
+            }
+        }
+    }
+}
+
+impl<D: DynDecisionHeuristic, C: DynConflictAnalysis> Iterator for ModelEnumerator<'_, D, C> {
+    type Item = Vec<bool>;
+
+    fn next(&mut self) -> Option<Vec<bool>> {
+        self.next_model()
+    }
+}
+
+/// The negations of the decisions the values of `variables` follow from along the reasons of
+/// `trail`. Literals of level zero follow from the formula alone.
+pub fn implying_decisions(formula: &Formula, trail: &Trail, variables: &[Variable]) -> Clause {
+    let mut marked = vec![false; trail.assignment.len()];
+
+    for &variable in variables {
+        marked[variable] = true;
+    }
+
+    let mut clause = vec![];
+
+    // reasons come before the literals they imply
+    for &(variable, reason) in trail.levels[1..]
+        .iter()
+        .rev()
+        .flat_map(|level| level.iter().rev())
+    {
+        if !marked[variable] {
+            continue;
+        }
+
+        match reason {
+            None => clause.push(!trail.to_literal(variable)),
+            Some(reason_id) => {
+                for literal in &formula[reason_id] {
+                    marked[variable_name(*literal)] = true;
+                }
+            }
+        }
+    }
+
+    clause
+}
@@ -0,0 +1,142 @@
+use crate::cdcl::first_uip::FirstUIP;
+use crate::cdcl::mincut::CutMinimal;
+use crate::cdcl::propagation::*;
+
+pub type HybridMinCut = Hybrid<FirstUIP, CutMinimal>;
+
+#[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct HybridStatistics {
+    pub conflicts: usize,
+    pub sampled: usize,
+    pub primary_wins: usize,
+    pub secondary_wins: usize,
+    pub ties: usize,
+}
+
+/// Runs `primary` on every conflict and, on every `sample_period`-th conflict, also `secondary`,
+/// keeping the clause with the smaller LBD, then the smaller size. Ties go to `primary`.
+#[derive(Debug, Default, Clone)]
+pub struct Hybrid<A: ConflictAnalysis, B: ConflictAnalysis> {
+    primary: A,
+    secondary: B,
+    sample_period: usize,
+    statistics: HybridStatistics,
+}
+
+impl<A: ConflictAnalysis, B: ConflictAnalysis> Hybrid<A, B> {
+    pub fn new(primary: A, secondary: B) -> Self {
+        Self {
+            primary,
+            secondary,
+            sample_period: 1,
+            statistics: Default::default(),
+        }
+    }
+
+    pub fn with_sample_period(mut self, sample_period: usize) -> Self {
+        assert_ne!(sample_period, 0);
+
+        self.sample_period = sample_period;
+        self
+    }
+
+    pub fn statistics(&self) -> &HybridStatistics {
+        &self.statistics
+    }
+}
+
+impl<A: ConflictAnalysis, B: ConflictAnalysis> ConflictAnalysis for Hybrid<A, B> {
+    fn from_formula(n: usize, formula: &Formula) -> Self {
+        Self::new(A::from_formula(n, formula), B::from_formula(n, formula))
+    }
+
+    fn analyze_conflict(&mut self, formula: &Formula, trail: &Trail, conflict: Clause) -> Clause {
+        self.statistics.conflicts += 1;
+
+        if !self.statistics.conflicts.is_multiple_of(self.sample_period) {
+            return self.primary.analyze_conflict(formula, trail, conflict);
+        }
+
+        self.statistics.sampled += 1;
+
+        let primary = self
+            .primary
+            .analyze_conflict(formula, trail, conflict.clone());
+        let secondary = self.secondary.analyze_conflict(formula, trail, conflict);
+
+        let score = |clause: &Clause| (trail.lbd(clause), clause.len());
+
+        match score(&secondary).cmp(&score(&primary)) {
+            std::cmp::Ordering::Less => {
+                self.statistics.secondary_wins += 1;
+                secondary
+            }
+            std::cmp::Ordering::Equal => {
+                self.statistics.ties += 1;
+                primary
+            }
+            std::cmp::Ordering::Greater => {
+                self.statistics.primary_wins += 1;
+                primary
+            }
+        }
+    }
+
+    fn backtrack_and_add_clause(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        level: usize,
+        clause_id: usize,
+    ) {
+        self.primary
+            .backtrack_and_add_clause(formula, trail, level, clause_id);
+        self.secondary
+            .backtrack_and_add_clause(formula, trail, level, clause_id);
+    }
+
+    fn backtrack(&mut self, formula: &Formula, trail: &Trail, level: usize) {
+        self.primary.backtrack(formula, trail, level);
+        self.secondary.backtrack(formula, trail, level);
+    }
+
+    fn simplify(&mut self, formula: &Formula, trail: &Trail) {
+        self.primary.simplify(formula, trail);
+        self.secondary.simplify(formula, trail);
+    }
+
+    fn named_statistics(&self) -> Vec<(&'static str, usize)> {
+        let HybridStatistics {
+            sampled,
+            primary_wins,
+            secondary_wins,
+            ties,
+            ..
+        } = self.statistics;
+
+        vec![
+            ("sampled conflicts", sampled),
+            ("primary wins", primary_wins),
+            ("secondary wins", secondary_wins),
+            ("ties", ties),
+        ]
+    }
+
+    fn propagate_literal(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        literal: Literal,
+        reason_id: usize,
+    ) {
+        self.primary
+            .propagate_literal(formula, trail, literal, reason_id);
+        self.secondary
+            .propagate_literal(formula, trail, literal, reason_id);
+    }
+
+    fn decide_literal(&mut self, formula: &Formula, trail: &Trail, literal: Literal) {
+        self.primary.decide_literal(formula, trail, literal);
+        self.secondary.decide_literal(formula, trail, literal);
+    }
+}
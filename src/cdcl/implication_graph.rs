@@ -0,0 +1,398 @@
+use std::fmt::Write;
+use std::mem;
+
+use crate::cdcl::propagation::{DynConflictAnalysis, Trail};
+use crate::cdcl::{variable_name, Clause, Formula, Literal};
+
+#[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum GraphFormat {
+    /// Graphviz DOT, one cluster per decision level.
+    #[default]
+    Dot,
+    Json,
+}
+
+/// An assigned literal of an [`ImplicationGraph`].
+#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct GraphVertex {
+    /// The literal made true.
+    pub literal: Literal,
+    pub level: usize,
+    pub is_decision: bool,
+    /// Weight the analysis gave to the literal, if it weighs them.
+    pub weight: Option<String>,
+}
+
+/// The part of the implication graph that reaches a conflict, with the clause learned from it.
+///
+/// Literals are written in DIMACS numbering by [`to_dot`](Self::to_dot) and
+/// [`to_json`](Self::to_json).
+#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct ImplicationGraph {
+    /// The literals in trail order.
+    pub vertices: Vec<GraphVertex>,
+    /// Indices of the vertices, from a literal of a reason to the literal it implied.
+    pub edges: Vec<(usize, usize)>,
+    /// The falsified clause.
+    pub conflict: Clause,
+    /// The learned clause, the negations of its literals are the cut.
+    pub learned: Clause,
+    /// Weight paid once if any literal of the level is cut, for the levels that have one.
+    pub level_weights: Vec<(usize, String)>,
+}
+
+impl ImplicationGraph {
+    /// The literals that can reach `conflict` along the reasons of `trail`, before the backjump.
+    pub fn from_trail(
+        formula: &Formula,
+        trail: &Trail,
+        conflict: &Clause,
+        learned: &Clause,
+    ) -> Self {
+        let mut reasons = vec![None; trail.assignment.len()];
+        let mut positions = vec![usize::MAX; trail.assignment.len()];
+
+        for (position, &(variable, reason)) in trail.levels.iter().flatten().enumerate() {
+            reasons[variable] = reason;
+            positions[variable] = position;
+        }
+
+        let mut in_cone = vec![false; trail.assignment.len()];
+        let mut cone = vec![];
+
+        for variable in conflict.iter().map(|literal| variable_name(*literal)) {
+            if !mem::replace(&mut in_cone[variable], true) {
+                cone.push(variable);
+            }
+        }
+
+        let mut id = 0;
+
+        while id < cone.len() {
+            let variable = cone[id];
+            id += 1;
+
+            for u in reasons[variable]
+                .iter()
+                .flat_map(|&reason_id| &formula[reason_id])
+                .map(|literal| variable_name(*literal))
+            {
+                if !mem::replace(&mut in_cone[u], true) {
+                    cone.push(u);
+                }
+            }
+        }
+
+        cone.sort_unstable_by_key(|&variable| positions[variable]);
+
+        // reuse `positions` for the indices of the vertices
+        for (index, &variable) in cone.iter().enumerate() {
+            positions[variable] = index;
+        }
+
+        let mut edges = vec![];
+
+        let vertices = cone
+            .iter()
+            .enumerate()
+            .map(|(index, &variable)| {
+                if let Some(reason_id) = reasons[variable] {
+                    for u in formula[reason_id]
+                        .iter()
+                        .map(|literal| variable_name(*literal))
+                    {
+                        if u != variable {
+                            edges.push((positions[u], index));
+                        }
+                    }
+                }
+
+                GraphVertex {
+                    literal: trail.to_literal(variable),
+                    level: trail.assignment[variable].decision_level(),
+                    is_decision: reasons[variable].is_none(),
+                    weight: None,
+                }
+            })
+            .collect();
+
+        Self {
+            vertices,
+            edges,
+            conflict: conflict.clone(),
+            learned: learned.clone(),
+            level_weights: vec![],
+        }
+    }
+
+    /// Whether the vertex `index` is cut, that is its negation is in the learned clause.
+    pub fn is_cut(&self, index: usize) -> bool {
+        self.learned.contains(&!self.vertices[index].literal)
+    }
+
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::Json => self.to_json(),
+        }
+    }
+
+    /// Graphviz DOT: decisions are boxes, the cut literals are red and the edges into the
+    /// conflict come from its falsified literals.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph implication_graph {\n    rankdir=LR;\n");
+        let mut levels: Vec<_> = self.vertices.iter().map(|vertex| vertex.level).collect();
+
+        levels.dedup();
+
+        for level in levels {
+            let mut label = format!("level {level}");
+
+            if let Some((_, weight)) = self.level_weights.iter().find(|(l, _)| *l == level) {
+                write!(label, ", weight {weight}").unwrap();
+            }
+
+            writeln!(dot, "    subgraph cluster_{level} {{").unwrap();
+            writeln!(dot, "        label={};", quote(&label)).unwrap();
+
+            for (index, vertex) in self.vertices.iter().enumerate() {
+                if vertex.level != level {
+                    continue;
+                }
+
+                let mut label = dimacs_literal(vertex.literal).to_string();
+
+                if let Some(weight) = &vertex.weight {
+                    write!(label, "\nweight {weight}").unwrap();
+                }
+
+                write!(dot, "        v{index} [label={}", quote(&label)).unwrap();
+
+                if vertex.is_decision {
+                    dot.push_str(", shape=box");
+                }
+
+                if self.is_cut(index) {
+                    dot.push_str(", color=red, penwidth=2");
+                }
+
+                dot.push_str("];\n");
+            }
+
+            dot.push_str("    }\n");
+        }
+
+        dot.push_str("    conflict [label=\"conflict\", shape=doublecircle];\n");
+
+        for (from, to) in &self.edges {
+            writeln!(dot, "    v{from} -> v{to};").unwrap();
+        }
+
+        for index in self.conflict_vertices() {
+            writeln!(dot, "    v{index} -> conflict;").unwrap();
+        }
+
+        dot.push_str("}\n");
+
+        dot
+    }
+
+    /// JSON object with the `vertices`, the `edges` as pairs of vertex ids, the `conflict` and
+    /// `learned` clauses and the `level_weights`. Weights are strings, they may not fit a number.
+    pub fn to_json(&self) -> String {
+        let vertices: Vec<_> = self
+            .vertices
+            .iter()
+            .enumerate()
+            .map(|(index, vertex)| {
+                format!(
+                    "{{\"id\": {index}, \"literal\": {}, \"level\": {}, \"decision\": {}, \
+                     \"weight\": {}, \"cut\": {}}}",
+                    dimacs_literal(vertex.literal),
+                    vertex.level,
+                    vertex.is_decision,
+                    vertex.weight.as_deref().map_or("null".to_string(), quote),
+                    self.is_cut(index),
+                )
+            })
+            .collect();
+
+        let edges: Vec<_> = self
+            .edges
+            .iter()
+            .map(|(from, to)| format!("[{from}, {to}]"))
+            .collect();
+
+        let level_weights: Vec<_> = self
+            .level_weights
+            .iter()
+            .map(|(level, weight)| format!("{{\"level\": {level}, \"weight\": {}}}", quote(weight)))
+            .collect();
+
+        format!(
+            "{{\n  \"vertices\": [\n    {}\n  ],\n  \"edges\": [{}],\n  \"conflict\": {},\n  \
+             \"learned\": {},\n  \"level_weights\": [{}]\n}}\n",
+            vertices.join(",\n    "),
+            edges.join(", "),
+            json_clause(&self.conflict),
+            json_clause(&self.learned),
+            level_weights.join(", "),
+        )
+    }
+
+    /// Vertices of the literals falsified by the conflict.
+    fn conflict_vertices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.vertices
+            .iter()
+            .enumerate()
+            .filter(|(_, vertex)| self.conflict.contains(&!vertex.literal))
+            .map(|(index, _)| index)
+    }
+}
+
+/// The literal as written in DIMACS files, variables counted from one.
+pub fn dimacs_literal(literal: Literal) -> isize {
+    if literal >= 0 {
+        literal + 1
+    } else {
+        literal
+    }
+}
+
+fn json_clause(clause: &Clause) -> String {
+    let literals: Vec<_> = clause
+        .iter()
+        .map(|&literal| dimacs_literal(literal).to_string())
+        .collect();
+
+    format!("[{}]", literals.join(", "))
+}
+
+/// A double-quoted string, valid in both DOT and JSON.
+fn quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+
+    quoted
+}
+
+/// Runs `analysis` and passes the implication graph of its `conflict`-th conflict, counted from
+/// one, to `dump`.
+pub struct DumpGraph<C: DynConflictAnalysis> {
+    analysis: C,
+    conflicts: usize,
+    conflict: usize,
+    dump: Box<dyn FnMut(&ImplicationGraph)>,
+}
+
+impl<C: DynConflictAnalysis> DumpGraph<C> {
+    pub fn new(
+        analysis: C,
+        conflict: usize,
+        dump: impl FnMut(&ImplicationGraph) + 'static,
+    ) -> Self {
+        Self {
+            analysis,
+            conflicts: 0,
+            conflict,
+            dump: Box::new(dump),
+        }
+    }
+
+    pub fn analysis(&self) -> &C {
+        &self.analysis
+    }
+}
+
+impl<C: DynConflictAnalysis> DynConflictAnalysis for DumpGraph<C> {
+    fn analyze_conflict(&mut self, formula: &Formula, trail: &Trail, conflict: Clause) -> Clause {
+        self.analyze_conflict_with_extras(formula, trail, conflict)
+            .0
+    }
+
+    fn analyze_conflict_with_extras(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        conflict: Clause,
+    ) -> (Clause, Vec<Clause>) {
+        self.conflicts += 1;
+
+        if self.conflicts != self.conflict {
+            return self
+                .analysis
+                .analyze_conflict_with_extras(formula, trail, conflict);
+        }
+
+        let (learned, extra) =
+            self.analysis
+                .analyze_conflict_with_extras(formula, trail, conflict.clone());
+
+        (self.dump)(
+            &self
+                .analysis
+                .implication_graph(formula, trail, &conflict, &learned),
+        );
+
+        (learned, extra)
+    }
+
+    fn implication_graph(
+        &self,
+        formula: &Formula,
+        trail: &Trail,
+        conflict: &Clause,
+        learned: &Clause,
+    ) -> ImplicationGraph {
+        self.analysis
+            .implication_graph(formula, trail, conflict, learned)
+    }
+
+    fn backtrack_and_add_clause(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        level: usize,
+        clause_id: usize,
+    ) {
+        self.analysis
+            .backtrack_and_add_clause(formula, trail, level, clause_id)
+    }
+
+    fn backtrack(&mut self, formula: &Formula, trail: &Trail, level: usize) {
+        self.analysis.backtrack(formula, trail, level);
+    }
+
+    fn simplify(&mut self, formula: &Formula, trail: &Trail) {
+        self.analysis.simplify(formula, trail);
+    }
+
+    fn named_statistics(&self) -> Vec<(&'static str, usize)> {
+        self.analysis.named_statistics()
+    }
+
+    fn propagate_literal(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        literal: Literal,
+        reason_id: usize,
+    ) {
+        self.analysis
+            .propagate_literal(formula, trail, literal, reason_id)
+    }
+
+    fn decide_literal(&mut self, formula: &Formula, trail: &Trail, literal: Literal) {
+        self.analysis.decide_literal(formula, trail, literal)
+    }
+}
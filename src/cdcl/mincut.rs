@@ -1,14 +1,27 @@
 pub mod functions;
 pub mod heuristic;
+pub mod level_weight;
 
 use heuristic::*;
-use num::BigUint;
-use std::usize;
+use level_weight::LevelWeight;
+
+/// The maximum flow backends a [`MinCutConflict`] can run on.
+pub use crate::sk1flow::{Dinic, MaxFlow, PushRelabel, SK1Flow};
 
 pub type CutMinimal = MinCutConflict<usize, functions::MinCutFunction>;
-pub type CutFirstUIP = MinCutConflict<usize, functions::FirstUIPFunction>;
+pub type CutKthUIP = MinCutConflict<usize, functions::KthUIPFunction>;
+pub type CutFirstUIP = CutKthUIP;
+pub type CutAllUIP = MinCutConflict<LevelWeight, functions::AllUIPFunction>;
+#[deprecated(note = "use `CutKthUIP` with `KthUIPFunction::new(2, 1)`")]
+#[allow(deprecated)]
 pub type CutSecondUIP = MinCutConflict<usize, functions::SecondUIPFunction>;
+#[deprecated(note = "use `CutKthUIP` with `KthUIPFunction::new(3, 1)`")]
+#[allow(deprecated)]
 pub type CutThirdUIP = MinCutConflict<usize, functions::ThirdUIPFunction>;
-pub type CutAllUIP = MinCutConflict<BigUint, functions::AllUIPFunction>;
+#[deprecated(note = "use `CutKthUIP` with `KthUIPFunction::new(KthUIPFunction::ALL, 1)`")]
+#[allow(deprecated)]
 pub type CutSatAllUIP = MinCutConflict<usize, functions::SaturatingAllUIPFunction>;
 pub type CutRelSat = MinCutConflict<usize, functions::RelSatFunction>;
+pub type CutLBD = MinCutConflict<usize, functions::LBDFunction>;
+pub type CutActivity = MinCutConflict<usize, functions::ActivityFunction>;
+pub type CutRealActivity = MinCutConflict<f64, functions::ActivityFunction>;
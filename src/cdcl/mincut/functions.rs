@@ -1,8 +1,10 @@
-use num::{BigUint, One, Zero};
+use num::{BigInt, BigRational, BigUint, One, Zero};
 
-use crate::cdcl::Formula;
+use crate::cdcl::propagation::Trail;
+use crate::cdcl::{variable_name, Formula};
 
-use super::WeightHeuristic;
+use super::level_weight::LevelWeight;
+use super::{Vertex, WeightHeuristic};
 
 #[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub struct MinCutFunction {}
@@ -16,7 +18,13 @@ impl WeightHeuristic<usize> for MinCutFunction {
         usize::MAX
     }
 
-    fn gen_vertex_weight(&self, level: usize, conflict_level: usize, _is_decision: bool) -> usize {
+    fn gen_vertex_weight(&self, vertex: &Vertex) -> usize {
+        let Vertex {
+            level,
+            conflict_level,
+            ..
+        } = *vertex;
+
         if level == conflict_level {
             usize::MAX / 2
         } else {
@@ -25,10 +33,52 @@ impl WeightHeuristic<usize> for MinCutFunction {
     }
 }
 
-#[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
-pub struct FirstUIPFunction {}
+/// Prefers cuts through the `k` deepest levels, each level weighing `decay` bits less than the
+/// next one. Lower levels are free, `k == KthUIPFunction::ALL` weighs every level.
+///
+/// The decay keeps one vertex of every level cheaper than a second vertex of a deeper level, and
+/// their sum below the source excess.
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct KthUIPFunction {
+    k: usize,
+    decay: u32,
+}
+
+impl KthUIPFunction {
+    pub const ALL: usize = usize::MAX;
+
+    /// # Panics
+    ///
+    /// If `k` or `decay` is zero.
+    pub fn new(k: usize, decay: u32) -> Self {
+        assert_ne!(k, 0);
+        assert_ne!(decay, 0, "the weights of the levels must decay");
+
+        Self { k, decay }
+    }
+
+    /// Number of times the weight of the vertex is halved from that of the conflict level,
+    /// `None` if the vertex is free.
+    fn halvings(&self, vertex: &Vertex) -> Option<u64> {
+        let Vertex {
+            level,
+            conflict_level,
+            ..
+        } = *vertex;
+
+        let diff = conflict_level - level;
 
-impl WeightHeuristic<usize> for FirstUIPFunction {
+        (level != 0 && diff < self.k).then(|| diff as u64 * self.decay as u64)
+    }
+}
+
+impl Default for KthUIPFunction {
+    fn default() -> Self {
+        Self::new(1, 1)
+    }
+}
+
+impl WeightHeuristic<usize> for KthUIPFunction {
     fn from_formula(_n: usize, _formula: &Formula) -> Self {
         Self::default()
     }
@@ -37,50 +87,139 @@ impl WeightHeuristic<usize> for FirstUIPFunction {
         usize::MAX
     }
 
-    fn gen_vertex_weight(&self, level: usize, conflict_level: usize, _is_decision: bool) -> usize {
-        if level == 0 {
-            return 0;
+    fn gen_vertex_weight(&self, vertex: &Vertex) -> usize {
+        match self.halvings(vertex) {
+            None => 0,
+            Some(halvings) => (usize::MAX / 2) >> halvings.min((usize::BITS - 1) as u64),
         }
+    }
+}
 
-        if level == conflict_level {
-            usize::MAX / 2
-        } else {
-            0
+/// The same weights relative to one for the conflict level. Those below
+/// [`FLOAT_EPSILON`](crate::sk1flow::FLOAT_EPSILON) are free, as are the levels more than about
+/// 23 bits of decay below the conflict.
+impl WeightHeuristic<f64> for KthUIPFunction {
+    fn from_formula(_n: usize, _formula: &Formula) -> Self {
+        Self::default()
+    }
+
+    fn source_excess(&self, _conflict_level: usize) -> f64 {
+        // more than one vertex of every level weighs
+        2.0
+    }
+
+    fn gen_vertex_weight(&self, vertex: &Vertex) -> f64 {
+        match self.halvings(vertex) {
+            None => 0.0,
+            Some(halvings) => 0.5f64.powi(halvings.min(i32::MAX as u64) as i32),
+        }
+    }
+}
+
+/// The same weights as exact rationals, no level is ever too deep to weigh.
+impl WeightHeuristic<BigRational> for KthUIPFunction {
+    fn from_formula(_n: usize, _formula: &Formula) -> Self {
+        Self::default()
+    }
+
+    fn source_excess(&self, _conflict_level: usize) -> BigRational {
+        BigRational::from_integer(2.into())
+    }
+
+    fn gen_vertex_weight(&self, vertex: &Vertex) -> BigRational {
+        match self.halvings(vertex) {
+            None => BigRational::zero(),
+            Some(halvings) => BigRational::new(BigInt::one(), BigInt::one() << halvings as usize),
         }
     }
 }
 
+/// [`KthUIPFunction`] with `k == 2`.
+#[deprecated(note = "use `KthUIPFunction::new(2, 1)`")]
 #[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub struct SecondUIPFunction {}
 
-impl WeightHeuristic<usize> for SecondUIPFunction {
+/// [`KthUIPFunction`] with `k == 3`.
+#[deprecated(note = "use `KthUIPFunction::new(3, 1)`")]
+#[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct ThirdUIPFunction {}
+
+/// [`KthUIPFunction`] weighing every level.
+#[deprecated(note = "use `KthUIPFunction::new(KthUIPFunction::ALL, 1)`")]
+#[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct SaturatingAllUIPFunction {}
+
+macro_rules! fixed_kth_uip_function {
+    ($function:ident, $k:expr) => {
+        #[allow(deprecated)]
+        impl WeightHeuristic<usize> for $function {
+            fn from_formula(_n: usize, _formula: &Formula) -> Self {
+                Self::default()
+            }
+
+            fn source_excess(&self, conflict_level: usize) -> usize {
+                KthUIPFunction::new($k, 1).source_excess(conflict_level)
+            }
+
+            fn gen_vertex_weight(&self, vertex: &Vertex) -> usize {
+                KthUIPFunction::new($k, 1).gen_vertex_weight(vertex)
+            }
+        }
+    };
+}
+
+fixed_kth_uip_function!(SecondUIPFunction, 2);
+fixed_kth_uip_function!(ThirdUIPFunction, 3);
+fixed_kth_uip_function!(SaturatingAllUIPFunction, KthUIPFunction::ALL);
+
+#[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct AllUIPFunction {}
+
+impl WeightHeuristic<BigUint> for AllUIPFunction {
     fn from_formula(_n: usize, _formula: &Formula) -> Self {
         Self::default()
     }
 
-    fn source_excess(&self, _conflict_level: usize) -> usize {
-        usize::MAX
+    fn source_excess(&self, conflict_level: usize) -> BigUint {
+        BigUint::from(4u32) << conflict_level
     }
 
-    fn gen_vertex_weight(&self, level: usize, conflict_level: usize, _is_decision: bool) -> usize {
+    fn gen_vertex_weight(&self, vertex: &Vertex) -> BigUint {
+        let Vertex { level, .. } = *vertex;
+
         if level == 0 {
-            return 0;
+            return BigUint::zero();
         }
 
-        let diff = conflict_level - level;
+        BigUint::one() << level
+    }
+}
 
-        if diff < 2 {
-            (usize::MAX / 2) >> diff
-        } else {
-            0
+/// The same weights without big integers.
+impl WeightHeuristic<LevelWeight> for AllUIPFunction {
+    fn from_formula(_n: usize, _formula: &Formula) -> Self {
+        Self::default()
+    }
+
+    fn source_excess(&self, conflict_level: usize) -> LevelWeight {
+        LevelWeight::level(conflict_level + 2)
+    }
+
+    fn gen_vertex_weight(&self, vertex: &Vertex) -> LevelWeight {
+        let Vertex { level, .. } = *vertex;
+
+        if level == 0 {
+            return LevelWeight::zero();
         }
+
+        LevelWeight::level(level)
     }
 }
 
 #[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
-pub struct ThirdUIPFunction {}
+pub struct RelSatFunction {}
 
-impl WeightHeuristic<usize> for ThirdUIPFunction {
+impl WeightHeuristic<usize> for RelSatFunction {
     fn from_formula(_n: usize, _formula: &Formula) -> Self {
         Self::default()
     }
@@ -89,89 +228,212 @@ impl WeightHeuristic<usize> for ThirdUIPFunction {
         usize::MAX
     }
 
-    fn gen_vertex_weight(&self, level: usize, conflict_level: usize, _is_decision: bool) -> usize {
+    fn gen_vertex_weight(&self, vertex: &Vertex) -> usize {
+        let Vertex {
+            level,
+            conflict_level,
+            is_decision,
+            ..
+        } = *vertex;
+
         if level == 0 {
             return 0;
         }
 
-        let diff = conflict_level - level;
-
-        if diff < 3 {
-            (usize::MAX / 2) >> diff
+        if (level == conflict_level) && !is_decision {
+            usize::MAX
         } else {
             0
         }
     }
 }
 
+/// Approximates the clause with the fewest decision levels. Every lower level gets a gate
+/// costing more than any clause size, so the cut pays mostly per level and then per literal.
+///
+/// The gates are shared by the literals of their level, so flow can also cross from one literal
+/// of a level to another through the gate. A cut may thus pay for a level it keeps no literal
+/// of, and the learned LBD is only bounded by the cost of the cut: it is never above that of the
+/// first UIP clause, whose cut costs one gate per level it contains, but need not be minimal.
+/// Level zero has no gate and its literals are left out of the clause.
 #[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
-pub struct SaturatingAllUIPFunction {}
+pub struct LBDFunction {
+    n: usize,
+}
 
-impl WeightHeuristic<usize> for SaturatingAllUIPFunction {
-    fn from_formula(_n: usize, _formula: &Formula) -> Self {
-        Self::default()
+impl WeightHeuristic<usize> for LBDFunction {
+    fn from_formula(n: usize, _formula: &Formula) -> Self {
+        Self { n }
     }
 
     fn source_excess(&self, _conflict_level: usize) -> usize {
         usize::MAX
     }
 
-    fn gen_vertex_weight(&self, level: usize, conflict_level: usize, _is_decision: bool) -> usize {
+    fn gen_vertex_weight(&self, vertex: &Vertex) -> usize {
+        let Vertex {
+            level,
+            conflict_level,
+            ..
+        } = *vertex;
+
+        // no flow reaches level zero, a positive weight keeps its literals out of the cut
         if level == 0 {
-            return 0;
+            return 1;
         }
 
-        (usize::MAX / 4) >> (conflict_level - level).min((usize::BITS - 1) as usize)
+        if level == conflict_level {
+            usize::MAX / 4
+        } else {
+            1
+        }
+    }
+
+    fn gen_level_weight(&self, level: usize, conflict_level: usize) -> Option<usize> {
+        // more than any clause size
+        (level != 0 && level != conflict_level).then_some(self.n + 1)
     }
 }
 
-#[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
-pub struct AllUIPFunction {}
+/// Weights vertices by the inverse of a VSIDS-style activity bumped on the learned clauses.
+#[derive(Debug, Default, Clone, PartialOrd, PartialEq)]
+pub struct ActivityFunction {
+    activity: Vec<f64>,
+    increment: f64,
+    max_activity: f64,
+}
 
-impl WeightHeuristic<BigUint> for AllUIPFunction {
-    fn from_formula(_n: usize, _formula: &Formula) -> Self {
-        Self::default()
+impl ActivityFunction {
+    const DECAY: f64 = 0.95;
+    const SCALE: f64 = (1 << 16) as f64;
+    const RANGE: f64 = 15.0;
+    const RESCALE_LIMIT: f64 = 1e100;
+
+    /// Cost of a literal below the conflict level, from 1 for the inactive variables down to
+    /// `1 / (1 + RANGE)` for the most active one.
+    fn cost(&self, variable: usize) -> f64 {
+        let relative = if self.max_activity > 0.0 {
+            self.activity[variable] / self.max_activity
+        } else {
+            0.0
+        };
+
+        1.0 / (1.0 + Self::RANGE * relative)
     }
 
-    fn source_excess(&self, conflict_level: usize) -> BigUint {
-        BigUint::from(4u32) << conflict_level
+    /// Cost of a conflict-level literal, more than all the others together.
+    fn conflict_cost(&self) -> f64 {
+        (self.activity.len() + 1) as f64
     }
 
-    fn gen_vertex_weight(
-        &self,
-        level: usize,
-        _conflict_level: usize,
-        _is_decision: bool,
-    ) -> BigUint {
-        if level == 0 {
-            return BigUint::zero();
+    fn update_activity(&mut self, formula: &Formula, clause_id: usize) {
+        for variable in formula[clause_id]
+            .iter()
+            .map(|literal| variable_name(*literal))
+        {
+            self.activity[variable] += self.increment;
+            self.max_activity = self.max_activity.max(self.activity[variable]);
         }
 
-        BigUint::one() << level
+        self.increment /= Self::DECAY;
+
+        if self.max_activity > Self::RESCALE_LIMIT {
+            for activity in &mut self.activity {
+                *activity /= Self::RESCALE_LIMIT;
+            }
+            self.increment /= Self::RESCALE_LIMIT;
+            self.max_activity /= Self::RESCALE_LIMIT;
+        }
     }
 }
 
-#[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
-pub struct RelSatFunction {}
-
-impl WeightHeuristic<usize> for RelSatFunction {
-    fn from_formula(_n: usize, _formula: &Formula) -> Self {
-        Self::default()
+impl WeightHeuristic<usize> for ActivityFunction {
+    fn from_formula(n: usize, _formula: &Formula) -> Self {
+        Self {
+            activity: vec![0.0; n],
+            increment: 1.0,
+            max_activity: 0.0,
+        }
     }
 
     fn source_excess(&self, _conflict_level: usize) -> usize {
         usize::MAX
     }
 
-    fn gen_vertex_weight(&self, level: usize, conflict_level: usize, is_decision: bool) -> usize {
+    fn gen_vertex_weight(&self, vertex: &Vertex) -> usize {
+        let Vertex {
+            variable,
+            level,
+            conflict_level,
+            ..
+        } = *vertex;
+
         if level == 0 {
             return 0;
         }
 
-        if (level == conflict_level) && !is_decision {
-            usize::MAX
-        } else {
-            0
+        if level == conflict_level {
+            return usize::MAX / 4;
         }
+
+        (Self::SCALE * self.cost(variable)).ceil() as usize
+    }
+
+    fn learn_clause(&mut self, formula: &Formula, _trail: &Trail, clause_id: usize) {
+        self.update_activity(formula, clause_id);
+    }
+}
+
+/// The costs themselves, without scaling nor rounding.
+impl WeightHeuristic<f64> for ActivityFunction {
+    fn from_formula(n: usize, formula: &Formula) -> Self {
+        <Self as WeightHeuristic<usize>>::from_formula(n, formula)
+    }
+
+    fn source_excess(&self, _conflict_level: usize) -> f64 {
+        // more than the decisions of the cone weigh together
+        2.0 * self.conflict_cost()
+    }
+
+    fn gen_vertex_weight(&self, vertex: &Vertex) -> f64 {
+        let Vertex {
+            variable,
+            level,
+            conflict_level,
+            ..
+        } = *vertex;
+
+        match level {
+            0 => 0.0,
+            _ if level == conflict_level => self.conflict_cost(),
+            _ => self.cost(variable),
+        }
+    }
+
+    fn learn_clause(&mut self, formula: &Formula, _trail: &Trail, clause_id: usize) {
+        self.update_activity(formula, clause_id);
+    }
+}
+
+/// The costs as exact rationals.
+impl WeightHeuristic<BigRational> for ActivityFunction {
+    fn from_formula(n: usize, formula: &Formula) -> Self {
+        <Self as WeightHeuristic<usize>>::from_formula(n, formula)
+    }
+
+    fn source_excess(&self, conflict_level: usize) -> BigRational {
+        let excess: f64 = WeightHeuristic::<f64>::source_excess(self, conflict_level);
+
+        BigRational::from_float(excess).unwrap()
+    }
+
+    fn gen_vertex_weight(&self, vertex: &Vertex) -> BigRational {
+        let weight: f64 = WeightHeuristic::<f64>::gen_vertex_weight(self, vertex);
+
+        BigRational::from_float(weight).unwrap()
+    }
+
+    fn learn_clause(&mut self, formula: &Formula, _trail: &Trail, clause_id: usize) {
+        self.update_activity(formula, clause_id);
     }
 }
@@ -1,190 +1,581 @@
+use std::fmt::Display;
 use std::mem;
 
-use num::Integer;
+use num::Zero;
 
+use crate::cdcl::first_uip::FirstUIP;
+use crate::cdcl::implication_graph::ImplicationGraph;
 use crate::cdcl::propagation::{ConflictAnalysis, Trail};
-use crate::cdcl::{variable_name, Clause, Formula, Literal};
-use crate::sk1flow::graph::WeightedEdge;
-use crate::sk1flow::SK1Flow;
-
-pub trait MinCutWeight:
-    Integer
-    + Clone
-    + Default
-    + for<'a> std::ops::AddAssign<&'a Self>
-    + for<'a> std::ops::SubAssign<&'a Self>
-{
+use crate::cdcl::{variable_name, Clause, Formula, Literal, Variable};
+use crate::sk1flow::{Capacity, MaxFlow, SK1Flow, VertexNetwork};
+
+/// Weights of the literals: exact integers or rationals, or floating-point numbers compared up to
+/// [`FLOAT_EPSILON`](crate::sk1flow::FLOAT_EPSILON). They are displayed in exported
+/// implication graphs.
+pub trait MinCutWeight: Capacity + Zero + Display {}
+
+impl<W: Capacity + Zero + Display> MinCutWeight for W {}
+
+#[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum CutSide {
+    /// The minimum cut closest to the conflict.
+    #[default]
+    Sink,
+    /// The minimum cut closest to the decisions.
+    Source,
 }
 
-impl<
-        W: Integer
-            + Clone
-            + Default
-            + for<'a> std::ops::AddAssign<&'a Self>
-            + for<'a> std::ops::SubAssign<&'a Self>,
-    > MinCutWeight for W
-{
+/// Which part of the implication graph the flow is computed on; all of them learn the same
+/// sink-side clause.
+#[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum FlowNetwork {
+    /// The network kept alongside the trail, only the cone of the conflict is reset.
+    #[default]
+    Incremental,
+    /// A fresh network holding only the cone of the conflict.
+    Cone,
+    /// The whole kept network, including literals that cannot reach the conflict.
+    Full,
 }
 
+/// Conflict analysis learning a minimum weight vertex cut of the implication graph.
+///
+/// The flow network is kept alongside the trail: every assigned literal is a vertex whose
+/// capacity is its weight, pushed when the literal is assigned and popped on backjump. At a
+/// conflict only the vertices that can reach it take part in the flow, the rest of the network
+/// is left untouched.
+///
+/// The weights depend on the conflict level, so every literal gets a capacity. A literal with a
+/// single parent, a decision or one implied by a binary clause, keeps it on the edge from that
+/// parent; the others are split in two in the underlying [`Network`](crate::sk1flow::Network),
+/// joined by an inner edge.
 #[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
-pub struct MinCutConflict<W: MinCutWeight, T: WeightHeuristic<W>> {
-    graph: Vec<Vec<(usize, W)>>,
-    parents: Vec<Vec<usize>>,
+pub struct MinCutConflict<W: MinCutWeight, T: WeightHeuristic<W>, F: MaxFlow<W> = SK1Flow<W>> {
+    network: VertexNetwork<W, F>,
+    /// Number of edges of the network before each literal was added.
+    edge_marks: Vec<usize>,
+    /// Number of vertices and edges of the network before the current conflict was added.
+    conflict_mark: Option<(usize, usize)>,
     labels: Vec<Literal>,
+    /// Reason of each literal, `None` for decisions.
+    reasons: Vec<Option<usize>>,
+    /// Vertex of the decision of each level, the first vertex for level zero.
+    level_starts: Vec<usize>,
     indices: Vec<usize>,
+    in_cone: Vec<bool>,
+    /// Vertex of each literal in the network of the current conflict.
+    flow_ids: Vec<usize>,
+    flow_network: FlowNetwork,
     weight_heuristic: T,
+    extra_clauses: Option<FirstUIP>,
+    cut_side: CutSide,
+    paranoid: bool,
+    non_asserting_source_cuts: usize,
 }
 
-impl<W: MinCutWeight, T: WeightHeuristic<W>> MinCutConflict<W, T> {
+impl<W: MinCutWeight, T: WeightHeuristic<W>, F: MaxFlow<W>> MinCutConflict<W, T, F> {
     const SINK: usize = 1;
     const SOURCE: usize = 0;
 
-    fn add_edge(&mut self, from: usize, to: usize) {
-        self.graph[from].push((to, W::zero()));
-        self.parents[to].push(from);
+    /// Also learn the clauses of the source-side minimum cut and of the first UIP.
+    pub fn with_extra_clauses(mut self, extra_clauses: bool) -> Self {
+        self.extra_clauses = extra_clauses.then(|| FirstUIP::new(self.indices.len()));
+        self
     }
 
-    fn add_literal(&mut self, literal: Literal, reason: Option<&Clause>) {
-        let in_id = self.graph.len();
-        let out_id = in_id + 1;
+    pub fn with_weight_heuristic(mut self, weight_heuristic: T) -> Self {
+        self.weight_heuristic = weight_heuristic;
+        self
+    }
+
+    /// Which of the extreme minimum cuts becomes the learned clause.
+    pub fn with_cut_side(mut self, cut_side: CutSide) -> Self {
+        self.cut_side = cut_side;
+        self
+    }
+
+    pub fn with_flow_network(mut self, flow_network: FlowNetwork) -> Self {
+        self.flow_network = flow_network;
+        self
+    }
+
+    /// Verify every flow and its minimum cut, panicking on a wrong one. Each check clones the
+    /// whole network, which costs time linear in the implication graph at every conflict.
+    pub fn with_paranoid(mut self, paranoid: bool) -> Self {
+        self.paranoid = paranoid;
+        self
+    }
+
+    /// The flow network kept alongside the trail.
+    pub fn network(&self) -> &VertexNetwork<W, F> {
+        &self.network
+    }
+
+    /// Number of conflicts where the source-side cut was not asserting and the sink side was used.
+    pub fn non_asserting_source_cuts(&self) -> usize {
+        self.non_asserting_source_cuts
+    }
+
+    fn add_literal(&mut self, formula: &Formula, literal: Literal, reason_id: Option<usize>) {
+        self.edge_marks.push(self.network.edge_count());
+
+        let parents: Vec<_> = match reason_id {
+            None => vec![Self::SOURCE],
+            Some(reason_id) => formula[reason_id]
+                .iter()
+                .copied()
+                .filter(|l| *l != literal)
+                .map(|l| self.indices[variable_name(l)])
+                .collect(),
+        };
+
+        // the capacities are only set at a conflict
+        let v = match parents[..] {
+            [parent] => self.network.add_vertex_from(parent, W::zero()),
+            _ => {
+                let v = self.network.add_vertex(Some(W::zero()));
+
+                for &u in &parents {
+                    self.network.add_edge(u, v, W::zero());
+                }
+
+                v
+            }
+        };
 
-        self.graph.push(vec![(out_id, W::zero())]);
-        self.parents.push(vec![]);
-        self.labels.push(literal);
-        self.graph.push(vec![]);
-        self.parents.push(vec![in_id]);
         self.labels.push(literal);
+        self.reasons.push(reason_id);
+        self.in_cone.push(false);
+        self.flow_ids.push(v);
+        self.indices[variable_name(literal)] = v;
+    }
 
-        self.indices[variable_name(literal)] = in_id;
+    /// Vertices of the literals that can reach the conflict, in trail order, marked in `in_cone`.
+    /// With [`FlowNetwork::Full`] this is every literal.
+    fn mark_cone(&mut self, conflict: &Clause) -> Vec<usize> {
+        if self.flow_network == FlowNetwork::Full {
+            self.in_cone.fill(true);
 
-        match reason {
-            None => self.add_edge(Self::SOURCE, in_id),
-            Some(clause) => {
-                for v in clause
-                    .iter()
-                    .copied()
-                    .filter(|l| *l != literal)
-                    .map(|v| variable_name(v))
-                {
-                    self.add_edge(self.indices[v] + 1, in_id);
+            return (2..self.network.vertex_count()).collect();
+        }
+
+        let mut cone = vec![];
+
+        for v in conflict
+            .iter()
+            .map(|literal| self.indices[variable_name(*literal)])
+        {
+            if !mem::replace(&mut self.in_cone[v], true) {
+                cone.push(v);
+            }
+        }
+
+        let mut id = 0;
+
+        while id < cone.len() {
+            let v = cone[id];
+            id += 1;
+
+            for (_, u) in self.network.in_edges(v) {
+                if u != Self::SOURCE && !mem::replace(&mut self.in_cone[u], true) {
+                    cone.push(u);
                 }
             }
         }
+
+        cone.sort_unstable();
+
+        cone
     }
 
-    fn pop_redundant_edges(&mut self, v: usize, trail: &Trail) {
-        if v >= self.graph.len() {
-            return;
+    fn unmark_cone(&mut self, cone: &[usize]) {
+        self.in_cone[Self::SOURCE] = false;
+        self.in_cone[Self::SINK] = false;
+
+        for &v in cone {
+            self.in_cone[v] = false;
         }
+    }
 
-        while let Some((u, _w)) = self.graph[v].last() {
-            if trail.assignment[variable_name(self.labels[*u])].is_unset() {
-                self.graph[v].pop();
+    /// Resets the edges leaving `v` in the cone: infinite inside it, zero to the rest of the
+    /// network. The cone is closed under parents, so this covers every edge entering it. An edge
+    /// carrying the capacity of a literal of the cone gets it with the other weights.
+    fn reset_edges(&mut self, v: usize, infinity: &W) {
+        let edges: Vec<_> = self.network.out_edges(v).collect();
+
+        for (id, u) in edges {
+            let capacity = if !self.in_cone[u] {
+                W::zero()
+            } else if self.network.inner_edge(u) == Some(id) {
+                continue;
             } else {
-                break;
+                infinity.clone()
+            };
+
+            self.network.set_capacity(id, capacity);
+        }
+    }
+
+    /// Weight of every literal of the cone, with the literals of each level.
+    fn vertex_weights(
+        &self,
+        formula: &Formula,
+        trail: &Trail,
+        cone: &[usize],
+    ) -> (Vec<W>, Vec<Vec<usize>>) {
+        let total_levels = trail.levels.len();
+        let conflict_level = total_levels - 1;
+
+        let mut weights = Vec::with_capacity(cone.len());
+        let mut level_vertices = vec![vec![]; total_levels];
+
+        for &v in cone {
+            let variable = variable_name(self.labels[v]);
+            let level = trail.assignment[variable].decision_level();
+            let reason = self.reasons[v];
+
+            weights.push(self.weight_heuristic.gen_vertex_weight(&Vertex {
+                literal: self.labels[v],
+                variable,
+                level,
+                conflict_level,
+                is_decision: reason.is_none(),
+                trail_position: v - 2,
+                reason: reason.map(|reason_id| &formula[reason_id]),
+                trail,
+            }));
+            level_vertices[level].push(v);
+        }
+
+        (weights, level_vertices)
+    }
+
+    /// Prepares the kept network for the flow of the current conflict.
+    fn reset_network(&mut self, cone: &[usize], weights: Vec<W>, infinity: &W) {
+        self.conflict_mark = Some((self.network.vertex_count(), self.network.edge_count()));
+
+        self.reset_edges(Self::SOURCE, infinity);
+        self.network.set_excess(Self::SOURCE, infinity.clone());
+        self.network.set_excess(Self::SINK, W::zero());
+
+        for (&v, weight) in cone.iter().zip(weights) {
+            self.reset_edges(v, infinity);
+            self.network.set_excess(v, W::zero());
+            self.network.set_vertex_capacity(v, weight);
+        }
+    }
+
+    /// A fresh network on the cone only, its vertices are recorded in `flow_ids`.
+    fn cone_network(
+        &mut self,
+        cone: &[usize],
+        weights: Vec<W>,
+        infinity: &W,
+    ) -> VertexNetwork<W, F> {
+        let mut network = VertexNetwork::default();
+        network.add_vertex(None);
+        network.add_vertex(None);
+        network.set_excess(Self::SOURCE, infinity.clone());
+
+        for (&v, weight) in cone.iter().zip(weights) {
+            let parents: Vec<_> = self
+                .network
+                .in_edges(v)
+                .map(|(_, u)| self.flow_ids[u])
+                .collect();
+
+            let id = match parents[..] {
+                [parent] => network.add_vertex_from(parent, weight),
+                _ => {
+                    let id = network.add_vertex(Some(weight));
+
+                    for &u in &parents {
+                        network.add_edge(u, id, infinity.clone());
+                    }
+
+                    id
+                }
+            };
+
+            self.flow_ids[v] = id;
+        }
+
+        network
+    }
+
+    /// Builds the flow network of the conflict, returns the cone and the network if it is not
+    /// the kept one.
+    fn build_flow(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        conflict: &Clause,
+    ) -> (Vec<usize>, Option<VertexNetwork<W, F>>) {
+        let conflict_level = trail.levels.len() - 1;
+
+        debug_assert_eq!(
+            self.network.vertex_count(),
+            trail.levels.iter().map(Vec::len).sum::<usize>() + 2
+        );
+
+        let cone = self.mark_cone(conflict);
+        let infinity = self.weight_heuristic.source_excess(conflict_level); // informally
+        let (weights, level_vertices) = self.vertex_weights(formula, trail, &cone);
+
+        self.in_cone[Self::SOURCE] = true;
+        self.in_cone[Self::SINK] = true;
+
+        let mut fresh = match self.flow_network {
+            FlowNetwork::Cone => Some(self.cone_network(&cone, weights, &infinity)),
+            FlowNetwork::Incremental | FlowNetwork::Full => {
+                for &v in &cone {
+                    self.flow_ids[v] = v;
+                }
+
+                self.reset_network(&cone, weights, &infinity);
+
+                None
             }
+        };
+
+        self.unmark_cone(&cone);
+
+        let network = fresh.as_mut().unwrap_or(&mut self.network);
+
+        for v in conflict
+            .iter()
+            .map(|literal| self.indices[variable_name(*literal)])
+        {
+            network.add_edge(self.flow_ids[v], Self::SINK, infinity.clone());
         }
+
+        // per-level gates, the level weight is paid once if the level is cut
+        for (level, vertices) in level_vertices.iter().enumerate() {
+            if vertices.is_empty() {
+                continue;
+            }
+
+            if let Some(weight) = self
+                .weight_heuristic
+                .gen_level_weight(level, conflict_level)
+            {
+                let gate = network.add_vertex(Some(weight));
+
+                for &v in vertices {
+                    network.add_bypass(self.flow_ids[v], gate, infinity.clone());
+                }
+            }
+        }
+
+        (cone, fresh)
     }
 
-    fn pop_redundant_vertex(&mut self, trail: &Trail) {
-        for v in self.parents.pop().unwrap() {
-            self.pop_redundant_edges(v, trail);
+    /// Removes the literals of the levels the trail lost. Literals assigned out of order after
+    /// them stay assigned, they are added back in the same order.
+    fn remove_unassigned(&mut self, formula: &Formula, trail: &Trail) {
+        if let Some((vertices, edges)) = self.conflict_mark.take() {
+            self.network.truncate(vertices, edges);
+        }
+
+        if let Some(&start) = self.level_starts.get(trail.levels.len()) {
+            self.level_starts.truncate(trail.levels.len());
+            self.readd_from(formula, trail, start);
+        }
+
+        debug_assert_eq!(self.network.vertex_count(), self.labels.len());
+    }
+
+    /// Removes the vertices from `start` on and adds back those still assigned, in the same order
+    /// and with the edges of their reasons as they are now.
+    fn readd_from(&mut self, formula: &Formula, trail: &Trail, start: usize) {
+        if start == self.labels.len() {
+            return;
+        }
+
+        let kept: Vec<_> = (start..self.labels.len())
+            .filter(|&v| !trail.assignment[variable_name(self.labels[v])].is_unset())
+            .map(|v| (self.labels[v], self.reasons[v]))
+            .collect();
+
+        let literals = start - 2;
+
+        self.network.truncate(start, self.edge_marks[literals]);
+        self.edge_marks.truncate(literals);
+        self.labels.truncate(start);
+        self.reasons.truncate(start);
+        self.in_cone.truncate(start);
+        self.flow_ids.truncate(start);
+
+        for (literal, reason_id) in kept {
+            self.add_literal(formula, literal, reason_id);
         }
-        self.graph.pop();
+    }
+
+    fn cut_clause(&self, cone: &[usize], cut: &[bool]) -> Clause {
+        let clause: Clause = cone
+            .iter()
+            .filter(|&&v| cut[self.flow_ids[v]])
+            .map(|&v| !self.labels[v])
+            .collect();
+
+        debug_assert!(!clause.is_empty());
+
+        clause
     }
 }
 
-impl<W: MinCutWeight, T: WeightHeuristic<W>> ConflictAnalysis for MinCutConflict<W, T> {
+impl<W: MinCutWeight, T: WeightHeuristic<W>, F: MaxFlow<W>> ConflictAnalysis
+    for MinCutConflict<W, T, F>
+{
     fn from_formula(n: usize, formula: &Formula) -> Self {
+        let mut network = VertexNetwork::default();
+        network.add_vertex(None);
+        network.add_vertex(None);
+
         Self {
-            graph: vec![vec![]; 2],
-            parents: vec![vec![]; 2],
+            network,
+            edge_marks: vec![],
+            conflict_mark: None,
             labels: vec![0; 2],
+            reasons: vec![None; 2],
+            level_starts: vec![2],
             indices: vec![0; n],
+            in_cone: vec![false; 2],
+            flow_ids: vec![Self::SOURCE, Self::SINK],
+            flow_network: FlowNetwork::Incremental,
             weight_heuristic: T::from_formula(n, formula),
+            extra_clauses: None,
+            cut_side: CutSide::Sink,
+            paranoid: false,
+            non_asserting_source_cuts: 0,
         }
     }
 
-    fn analyze_conflict(&mut self, _formula: &Formula, trail: &Trail, conflict: Clause) -> Clause {
-        let total_levels = trail.levels.len();
-        let conflict_level = total_levels - 1;
+    fn analyze_conflict(&mut self, formula: &Formula, trail: &Trail, conflict: Clause) -> Clause {
+        self.analyze_conflict_with_extras(formula, trail, conflict)
+            .0
+    }
 
-        for v in conflict.iter().map(|literal| variable_name(*literal)) {
-            self.add_edge(self.indices[v] + 1, Self::SINK);
+    fn analyze_conflict_with_extras(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        conflict: Clause,
+    ) -> (Clause, Vec<Clause>) {
+        let (cone, mut fresh) = self.build_flow(formula, trail, &conflict);
+        let network = fresh.as_mut().unwrap_or(&mut self.network);
+
+        if self.paranoid {
+            if let Err(error) = network.certified_flow(Self::SINK) {
+                panic!("wrong flow at a conflict: {error}");
+            }
+        } else {
+            network.flow(Self::SINK);
         }
 
-        let mut excess = vec![W::zero(); self.graph.len()];
-        excess[0] = self.weight_heuristic.source_excess(conflict_level); // infinity, informally
+        let sink_cut = network.vertex_cut(Self::SINK);
+        let source_cut = (self.cut_side == CutSide::Source || self.extra_clauses.is_some())
+            .then(|| network.source_vertex_cut(Self::SOURCE));
 
-        // vertex weights
-        for v in (2..self.graph.len()).step_by(2) {
-            let variable = variable_name(self.labels[v]);
-            let level = trail.assignment[variable].decision_level();
+        let sink_side = self.cut_clause(&cone, &sink_cut);
+        let source_side = source_cut.map(|cut| self.cut_clause(&cone, &cut));
 
-            debug_assert_eq!(self.graph[v].len(), 1);
+        let (clause, other_side) = match (self.cut_side, source_side) {
+            (CutSide::Source, Some(source_side)) => {
+                if is_asserting(&source_side, trail) {
+                    (source_side, Some(sink_side))
+                } else {
+                    self.non_asserting_source_cuts += 1;
 
-            *self.graph[v][0].weight_mut() = self.weight_heuristic.gen_vertex_weight(
-                level,
-                conflict_level,
-                trail.levels[level][0].0 == variable,
-            );
-        }
+                    (sink_side, Some(source_side))
+                }
+            }
+            (_, other_side) => (sink_side, other_side),
+        };
 
-        // edges from source to decision variables, all infinite
-        for edge in &mut self.graph[Self::SOURCE] {
-            *edge.weight_mut() = excess[0].clone();
-        }
+        let mut extra = vec![];
 
-        for v in (3..self.graph.len()).step_by(2) {
-            for edge in &mut self.graph[v] {
-                *edge.weight_mut() = excess[0].clone()
+        if let Some(first_uip) = &mut self.extra_clauses {
+            let first_uip = first_uip.analyze_conflict(formula, trail, conflict);
+
+            for candidate in other_side.into_iter().chain([first_uip]) {
+                if !extra
+                    .iter()
+                    .chain([&clause])
+                    .any(|known| is_same_clause(known, &candidate))
+                {
+                    extra.push(candidate);
+                }
             }
         }
 
-        let mut flow = SK1Flow::from_graph(&self.graph, excess);
-        flow.flow(Self::SINK);
-        let cut = flow.cut(Self::SINK);
-
-        debug_assert!(cut[Self::SINK] && !cut[Self::SOURCE]);
+        (clause, extra)
+    }
 
-        let mut clause = vec![];
+    /// The graph of the trail, with the weights of the literals and the levels.
+    fn implication_graph(
+        &self,
+        formula: &Formula,
+        trail: &Trail,
+        conflict: &Clause,
+        learned: &Clause,
+    ) -> ImplicationGraph {
+        let mut graph = ImplicationGraph::from_trail(formula, trail, conflict, learned);
+        let conflict_level = trail.levels.len() - 1;
+
+        let cone: Vec<_> = graph
+            .vertices
+            .iter()
+            .map(|vertex| self.indices[variable_name(vertex.literal)])
+            .collect();
+        let (weights, level_vertices) = self.vertex_weights(formula, trail, &cone);
+
+        for (vertex, weight) in graph.vertices.iter_mut().zip(weights) {
+            vertex.weight = Some(weight.to_string());
+        }
 
-        for v in (2..cut.len()).step_by(2) {
-            if cut[v] != cut[v + 1] {
-                debug_assert!(cut[v + 1]);
+        for (level, vertices) in level_vertices.iter().enumerate() {
+            if vertices.is_empty() {
+                continue;
+            }
 
-                clause.push(!self.labels[v]);
+            if let Some(weight) = self
+                .weight_heuristic
+                .gen_level_weight(level, conflict_level)
+            {
+                graph.level_weights.push((level, weight.to_string()));
             }
         }
 
-        debug_assert!(!clause.is_empty());
-
-        clause
+        graph
     }
 
     fn backtrack_and_add_clause(
         &mut self,
-        _formula: &Formula,
+        formula: &Formula,
         trail: &Trail,
         _level: usize,
-        _clause_id: usize,
+        clause_id: usize,
     ) {
-        for v in mem::replace(&mut self.parents[Self::SINK], Vec::new()) {
-            let (to, _w) = self.graph[v].pop().unwrap();
+        self.weight_heuristic
+            .learn_clause(formula, trail, clause_id);
 
-            debug_assert_eq!(to, Self::SINK)
-        }
+        self.remove_unassigned(formula, trail);
+    }
 
-        while self.graph.len() > 2
-            && trail.assignment[variable_name(self.labels[self.graph.len() - 1])].is_unset()
-        {
-            self.pop_redundant_vertex(trail);
+    fn backtrack(&mut self, formula: &Formula, trail: &Trail, _level: usize) {
+        self.remove_unassigned(formula, trail);
+    }
+
+    /// Adds the literals of level zero back with their reasons as the simplification left them.
+    fn simplify(&mut self, formula: &Formula, trail: &Trail) {
+        if let Some((vertices, edges)) = self.conflict_mark.take() {
+            self.network.truncate(vertices, edges);
         }
-        self.labels.truncate(self.graph.len());
-        self.parents.truncate(self.graph.len());
 
-        debug_assert_eq!(self.graph.len(), self.parents.len());
-        debug_assert_eq!(self.graph.len(), self.labels.len());
+        self.readd_from(formula, trail, 2);
+
+        debug_assert_eq!(self.network.vertex_count(), self.labels.len());
     }
 
     fn propagate_literal(
@@ -194,16 +585,61 @@ impl<W: MinCutWeight, T: WeightHeuristic<W>> ConflictAnalysis for MinCutConflict
         literal: Literal,
         reason_id: usize,
     ) {
-        self.add_literal(literal, Some(&formula[reason_id]));
+        self.add_literal(formula, literal, Some(reason_id));
     }
 
-    fn decide_literal(&mut self, _formula: &Formula, _trail: &Trail, literal: Literal) {
-        self.add_literal(literal, None);
+    fn decide_literal(&mut self, formula: &Formula, _trail: &Trail, literal: Literal) {
+        self.level_starts.push(self.labels.len());
+        self.add_literal(formula, literal, None);
     }
 }
 
-pub trait WeightHeuristic<W: Integer>: Default {
+fn is_asserting(clause: &Clause, trail: &Trail) -> bool {
+    let conflict_level = trail.levels.len() - 1;
+
+    clause
+        .iter()
+        .filter(|literal| {
+            trail.assignment[variable_name(**literal)].decision_level() == conflict_level
+        })
+        .count()
+        == 1
+}
+
+fn is_same_clause(a: &Clause, b: &Clause) -> bool {
+    let mut a = a.clone();
+    let mut b = b.clone();
+
+    a.sort_unstable();
+    b.sort_unstable();
+
+    a == b
+}
+
+/// A literal of the implication graph, as seen by a [`WeightHeuristic`].
+#[derive(Debug, Copy, Clone)]
+pub struct Vertex<'a> {
+    pub literal: Literal,
+    pub variable: Variable,
+    pub level: usize,
+    pub conflict_level: usize,
+    pub is_decision: bool,
+    /// Index of the literal in assignment order, all levels included.
+    pub trail_position: usize,
+    pub reason: Option<&'a Clause>,
+    pub trail: &'a Trail,
+}
+
+pub trait WeightHeuristic<W: MinCutWeight>: Default {
     fn from_formula(n: usize, formula: &Formula) -> Self;
     fn source_excess(&self, conflict_level: usize) -> W;
-    fn gen_vertex_weight(&self, level: usize, conflict_level: usize, is_decision: bool) -> W;
+    fn gen_vertex_weight(&self, vertex: &Vertex) -> W;
+
+    /// Weight paid once if any literal of the level is in the clause, `None` for no such weight.
+    fn gen_level_weight(&self, _level: usize, _conflict_level: usize) -> Option<W> {
+        None
+    }
+
+    /// Called for every learned clause, right after the backjump.
+    fn learn_clause(&mut self, _formula: &Formula, _trail: &Trail, _clause_id: usize) {}
 }
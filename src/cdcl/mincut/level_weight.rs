@@ -0,0 +1,196 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, AddAssign, SubAssign};
+
+use num::Zero;
+
+use crate::sk1flow::Capacity;
+
+/// A sum of weights `2^level`, that is a binary number whose bit `i` stands for level `i`, so
+/// that the weights compare lexicographically from the highest level down and a level outweighs
+/// all the lower ones together.
+///
+/// The same numbers as [`BigUint`](num::BigUint) without allocating: the words of the first
+/// `64 * INLINE` levels are stored inline, only deeper levels spill to the heap.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct LevelWeight {
+    inline: [u64; Self::INLINE],
+    /// Words above the inline ones, without trailing zero words.
+    spill: Vec<u64>,
+}
+
+impl LevelWeight {
+    const INLINE: usize = 4;
+
+    /// The weight `2^level`.
+    pub fn level(level: usize) -> Self {
+        let mut weight = Self::default();
+        *weight.word_mut(level / 64) = 1 << (level % 64);
+
+        weight
+    }
+
+    fn len(&self) -> usize {
+        Self::INLINE + self.spill.len()
+    }
+
+    fn word(&self, index: usize) -> u64 {
+        match index.checked_sub(Self::INLINE) {
+            None => self.inline[index],
+            Some(index) => self.spill.get(index).copied().unwrap_or(0),
+        }
+    }
+
+    fn word_mut(&mut self, index: usize) -> &mut u64 {
+        match index.checked_sub(Self::INLINE) {
+            None => &mut self.inline[index],
+            Some(index) => {
+                if self.spill.len() <= index {
+                    self.spill.resize(index + 1, 0);
+                }
+
+                &mut self.spill[index]
+            }
+        }
+    }
+
+    fn trim(&mut self) {
+        while self.spill.last() == Some(&0) {
+            self.spill.pop();
+        }
+    }
+}
+
+impl Ord for LevelWeight {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self.spill.is_empty() && other.spill.is_empty() {
+            return self.inline.iter().rev().cmp(other.inline.iter().rev());
+        }
+
+        (0..self.len().max(other.len()))
+            .rev()
+            .map(|index| self.word(index).cmp(&other.word(index)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for LevelWeight {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl AddAssign<&LevelWeight> for LevelWeight {
+    fn add_assign(&mut self, other: &LevelWeight) {
+        let mut carry = false;
+
+        for (word, other) in self.inline.iter_mut().zip(other.inline) {
+            let (sum, overflow) = word.overflowing_add(other);
+            let (sum, carry_overflow) = sum.overflowing_add(carry as u64);
+
+            *word = sum;
+            carry = overflow || carry_overflow;
+        }
+
+        for index in Self::INLINE..other.len() {
+            let (sum, overflow) = self.word(index).overflowing_add(other.word(index));
+            let (sum, carry_overflow) = sum.overflowing_add(carry as u64);
+
+            *self.word_mut(index) = sum;
+            carry = overflow || carry_overflow;
+        }
+
+        let mut index = other.len();
+
+        while carry {
+            let (sum, overflow) = self.word(index).overflowing_add(1);
+
+            *self.word_mut(index) = sum;
+            carry = overflow;
+            index += 1;
+        }
+
+        self.trim();
+    }
+}
+
+impl SubAssign<&LevelWeight> for LevelWeight {
+    /// `other` must not be larger, as with the unsigned integers.
+    fn sub_assign(&mut self, other: &LevelWeight) {
+        debug_assert!(*self >= *other, "subtraction overflow");
+
+        let mut borrow = false;
+
+        for (word, other) in self.inline.iter_mut().zip(other.inline) {
+            let (difference, overflow) = word.overflowing_sub(other);
+            let (difference, borrow_overflow) = difference.overflowing_sub(borrow as u64);
+
+            *word = difference;
+            borrow = overflow || borrow_overflow;
+        }
+
+        for index in Self::INLINE..other.len() {
+            let (difference, overflow) = self.word(index).overflowing_sub(other.word(index));
+            let (difference, borrow_overflow) = difference.overflowing_sub(borrow as u64);
+
+            *self.word_mut(index) = difference;
+            borrow = overflow || borrow_overflow;
+        }
+
+        let mut index = other.len();
+
+        while borrow {
+            let (difference, overflow) = self.word(index).overflowing_sub(1);
+
+            *self.word_mut(index) = difference;
+            borrow = overflow;
+            index += 1;
+        }
+
+        self.trim();
+    }
+}
+
+/// The levels of the set bits, as `2^l1 + 2^l2 + ...` from the highest level down.
+impl fmt::Display for LevelWeight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        let mut separator = "";
+
+        for index in (0..self.len()).rev() {
+            let word = self.word(index);
+
+            for bit in (0..64).rev().filter(|bit| word >> bit & 1 == 1) {
+                write!(f, "{separator}2^{}", 64 * index + bit)?;
+                separator = " + ";
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Add for LevelWeight {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self += &other;
+        self
+    }
+}
+
+impl Zero for LevelWeight {
+    fn zero() -> Self {
+        Self::default()
+    }
+
+    fn is_zero(&self) -> bool {
+        self.inline == [0; Self::INLINE] && self.spill.is_empty()
+    }
+}
+
+impl Capacity for LevelWeight {}
@@ -1,18 +1,133 @@
 pub mod trail;
 
 pub use trail::*;
+
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::{fmt, mem};
+
+use crate::cdcl::implication_graph::ImplicationGraph;
 use ClauseType::*;
 use VariableState::*;
 
-#[derive(Debug)]
-pub struct CDCL<'a, D: DecisionHeuristic, C: ConflictAnalysis> {
+/// Options of the search itself, apart from its heuristics.
+#[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct SolverOptions {
+    /// Backtrack a single level instead of backjumping over more than this many levels, the
+    /// asserting literal is then assigned out of order on its own level, as in chronological
+    /// backtracking of Nadel and Ryvchin. `None` always backjumps.
+    pub chronological_backtracking: Option<usize>,
+    /// Restart after this many conflicts times the Luby sequence 1, 1, 2, 1, 1, 2, 4, ...,
+    /// keeping the levels the decision heuristic would decide again. `None` never restarts.
+    pub restart_interval: Option<usize>,
+    /// Whenever the search is back at level zero with new literals there, remove the clauses
+    /// they satisfy and the literals they falsify, from both original and learned clauses.
+    pub simplify: bool,
+}
+
+#[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct SolverStatistics {
+    pub conflicts: usize,
+    pub decisions: usize,
+    pub restarts: usize,
+    /// Levels kept by restarts, summed over all of them.
+    pub reused_levels: usize,
+    pub simplifications: usize,
+    /// Clauses satisfied at level zero that simplifications stopped watching.
+    pub unwatched_clauses: usize,
+    /// Literals false at level zero removed from the remaining clauses.
+    pub removed_literals: usize,
+}
+
+/// How a search ended.
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum SolveResult {
+    Satisfiable,
+    Unsatisfiable,
+    /// The terminate handle was set, see [`CDCL::with_terminate`].
+    Interrupted,
+}
+
+/// State of the search when a progress event is reported.
+#[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct ProgressEvent {
+    pub conflicts: usize,
+    pub decisions: usize,
+    pub learned_clauses: usize,
+    /// Assigned variables.
+    pub trail_size: usize,
+    pub level: usize,
+}
+
+/// Observer of the search, see [`CDCL::with_progress`]. Implemented for closures.
+pub trait Progress {
+    fn progress(&mut self, event: &ProgressEvent);
+}
+
+impl<F: FnMut(&ProgressEvent)> Progress for F {
+    fn progress(&mut self, event: &ProgressEvent) {
+        self(event)
+    }
+}
+
+/// The `i`-th element of the Luby sequence, counted from one.
+fn luby(mut i: usize) -> usize {
+    loop {
+        // the sequence up to 2^k - 1 ends with 2^(k-1), and repeats itself before
+        let k = usize::BITS - i.leading_zeros();
+
+        if i == (1 << k) - 1 {
+            return 1 << (k - 1);
+        }
+
+        i -= (1 << (k - 1)) - 1;
+    }
+}
+
+pub struct CDCL<'a, D: DynDecisionHeuristic, C: DynConflictAnalysis> {
     trail: Trail,
     formula: &'a mut Formula,
     decision_heuristic: D,
     conflict_analysis: C,
+    options: SolverOptions,
+    /// Assigned variables whose watches are yet to be processed.
+    queue: VecDeque<Variable>,
+    statistics: SolverStatistics,
+    /// Conflicts left before the next restart.
+    restart_countdown: usize,
+    /// Literals of level zero when the clauses were last simplified.
+    simplified_literals: usize,
+    /// Clauses of the formula before learning.
+    original_clauses: usize,
+    terminate: Option<Arc<AtomicBool>>,
+    /// Conflicts between two reports, and where to report.
+    progress: Option<(usize, Box<dyn Progress>)>,
+    /// Whether the clauses were preprocessed by a first search.
+    started: bool,
+    /// Unit clauses learned above level zero, to propagate there.
+    pending_units: Vec<usize>,
+}
+
+impl<D: DynDecisionHeuristic + fmt::Debug, C: DynConflictAnalysis + fmt::Debug> fmt::Debug
+    for CDCL<'_, D, C>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CDCL")
+            .field("trail", &self.trail)
+            .field("formula", &self.formula)
+            .field("decision_heuristic", &self.decision_heuristic)
+            .field("conflict_analysis", &self.conflict_analysis)
+            .field("options", &self.options)
+            .field("queue", &self.queue)
+            .field("statistics", &self.statistics)
+            .field("terminate", &self.terminate)
+            .finish_non_exhaustive()
+    }
 }
 
-impl<'a, D: DecisionHeuristic, C: ConflictAnalysis> CDCL<'a, D, C> {
+impl<'a, D: DynDecisionHeuristic, C: DynConflictAnalysis> CDCL<'a, D, C> {
     pub fn new(
         n: usize,
         formula: &'a mut Formula,
@@ -21,12 +136,47 @@ impl<'a, D: DecisionHeuristic, C: ConflictAnalysis> CDCL<'a, D, C> {
     ) -> CDCL<'a, D, C> {
         CDCL::<'a> {
             trail: Trail::new(n, formula.len()),
+            original_clauses: formula.len(),
             formula,
             decision_heuristic,
             conflict_analysis,
+            options: SolverOptions::default(),
+            queue: VecDeque::new(),
+            statistics: SolverStatistics::default(),
+            restart_countdown: 0,
+            simplified_literals: 0,
+            terminate: None,
+            progress: None,
+            started: false,
+            pending_units: vec![],
         }
     }
 
+    pub fn with_options(mut self, options: SolverOptions) -> Self {
+        self.options = options;
+        self.restart_countdown = options.restart_interval.unwrap_or(0);
+        self
+    }
+
+    /// Stops the search at the next conflict or decision once `terminate` is set, from any
+    /// thread. The search then returns [`SolveResult::Interrupted`] and cannot be resumed.
+    pub fn with_terminate(mut self, terminate: Arc<AtomicBool>) -> Self {
+        self.terminate = Some(terminate);
+        self
+    }
+
+    /// Reports the progress of the search to `progress` every `interval` conflicts.
+    pub fn with_progress(mut self, interval: usize, progress: impl Progress + 'static) -> Self {
+        assert_ne!(interval, 0);
+
+        self.progress = Some((interval, Box::new(progress)));
+        self
+    }
+
+    pub fn statistics(&self) -> &SolverStatistics {
+        &self.statistics
+    }
+
     pub fn get_assignment(&self) -> Vec<bool> {
         self.trail
             .assignment
@@ -35,6 +185,23 @@ impl<'a, D: DecisionHeuristic, C: ConflictAnalysis> CDCL<'a, D, C> {
             .collect()
     }
 
+    pub fn trail(&self) -> &Trail {
+        &self.trail
+    }
+
+    /// The clauses, the learned ones after the original ones.
+    pub fn formula(&self) -> &Formula {
+        self.formula
+    }
+
+    pub fn decision_heuristic(&self) -> &D {
+        &self.decision_heuristic
+    }
+
+    pub fn conflict_analysis(&self) -> &C {
+        &self.conflict_analysis
+    }
+
     fn add_learned_clause(&mut self, clause: Clause, clause_type: ClauseType) {
         self.formula.push(clause);
         self.trail.clause_types.push(clause_type);
@@ -59,11 +226,7 @@ impl<'a, D: DecisionHeuristic, C: ConflictAnalysis> CDCL<'a, D, C> {
                     Falsified => {
                         ans = Unit(literal);
                     }
-                    Unit(known) => {
-                        if literal != known {
-                            ans = Watched(known, literal)
-                        }
-                    }
+                    Unit(known) if literal != known => ans = Watched(known, literal),
                     _ => {}
                 },
                 False(_) => {
@@ -105,24 +268,166 @@ impl<'a, D: DecisionHeuristic, C: ConflictAnalysis> CDCL<'a, D, C> {
         true
     }
 
+    /// Level to backtrack to from the current one with a clause asserting on `back_level`.
+    fn backtrack_level(&self, back_level: usize) -> usize {
+        let current_level = self.trail.levels.len() - 1;
+
+        match self.options.chronological_backtracking {
+            Some(threshold) if current_level - back_level > threshold => current_level - 1,
+            _ => back_level,
+        }
+    }
+
+    /// Unassigns the levels above `level`, the pending literals below it stay queued.
+    fn backtrack(&mut self, level: usize) {
+        self.trail.backtrack(level);
+
+        let assignment = &self.trail.assignment;
+        self.queue
+            .retain(|&variable| !assignment[variable].is_unset());
+    }
+
+    /// Restarts if enough conflicts passed. The levels whose decisions come before the best
+    /// unassigned variable in the order of the decision heuristic would be decided and propagated
+    /// again the same way, so they are kept.
+    fn restart_if_due(&mut self) {
+        let Some(interval) = self.options.restart_interval else {
+            return;
+        };
+
+        if self.restart_countdown > 0 {
+            return;
+        }
+
+        self.statistics.restarts += 1;
+        self.restart_countdown = interval * luby(self.statistics.restarts + 1);
+
+        let decision_heuristic = &self.decision_heuristic;
+        let next = (0..self.trail.assignment.len())
+            .filter(|&variable| self.trail.assignment[variable].is_unset())
+            .reduce(
+                |best, variable| match decision_heuristic.decides_before(variable, best) {
+                    true => variable,
+                    false => best,
+                },
+            );
+
+        let Some(next) = next else {
+            return;
+        };
+
+        let reused = self.trail.levels[1..]
+            .iter()
+            .take_while(|level| decision_heuristic.decides_before(level[0].0, next))
+            .count();
+
+        self.statistics.reused_levels += reused;
+
+        if reused + 1 < self.trail.levels.len() {
+            self.backtrack_and_notify(reused);
+        }
+    }
+
+    /// Simplifies the clauses if the search is at level zero and it gained literals since the
+    /// last time. Returns `false` if the formula is unsatisfiable.
+    fn simplify_if_due(&mut self) -> bool {
+        if !self.options.simplify
+            || self.trail.levels.len() > 1
+            || self.trail.levels[0].len() == self.simplified_literals
+        {
+            return true;
+        }
+
+        self.simplify()
+    }
+
+    /// Stops watching the clauses satisfied at level zero, which stay as they are and keep the
+    /// `Satisfied` type, and removes the literals false there from the others before watching
+    /// them again. Clause ids do not change and the formula stays satisfied by every model found.
+    /// A clause left with a single literal is propagated. Returns `false` if a clause is left
+    /// empty.
+    fn simplify(&mut self) -> bool {
+        debug_assert_eq!(self.trail.levels.len(), 1);
+
+        self.statistics.simplifications += 1;
+        self.simplified_literals = self.trail.levels[0].len();
+
+        for watches in &mut self.trail.watches {
+            watches[0].clear();
+            watches[1].clear();
+        }
+
+        for clause_id in 0..self.formula.len() {
+            let assignment = &self.trail.assignment;
+            let clause = &mut self.formula[clause_id];
+
+            let is_satisfied = |literal: &Literal| match assignment[variable_name(*literal)] {
+                Unset => false,
+                state => state.bool_value() == (*literal >= 0),
+            };
+
+            if self.trail.clause_types[clause_id] == Satisfied {
+                continue;
+            }
+
+            if clause.iter().any(is_satisfied) {
+                self.statistics.unwatched_clauses += 1;
+                self.trail.clause_types[clause_id] = Satisfied;
+
+                continue;
+            }
+
+            let size = clause.len();
+
+            clause.retain(|literal| assignment[variable_name(*literal)].is_unset());
+            self.statistics.removed_literals += size - clause.len();
+
+            self.trail.clause_types[clause_id] =
+                self.get_clause_type(&self.formula[clause_id], None);
+
+            match self.trail.clause_types[clause_id] {
+                Unwatched | Satisfied => {
+                    unreachable!()
+                }
+                Falsified => {
+                    return false;
+                }
+                Unit(literal) => self.propagate_literal(literal, clause_id),
+                Watched(a, b) => {
+                    self.trail.add_watch(a, clause_id);
+                    self.trail.add_watch(b, clause_id);
+                }
+            }
+        }
+
+        self.conflict_analysis.simplify(self.formula, &self.trail);
+
+        true
+    }
+
+    /// Backtracks without learning a clause.
+    fn backtrack_and_notify(&mut self, level: usize) {
+        self.backtrack(level);
+
+        self.decision_heuristic
+            .backtrack(self.formula, &self.trail, level);
+        self.conflict_analysis
+            .backtrack(self.formula, &self.trail, level);
+    }
+
     fn backtrack_and_add_uip_clause(&mut self, clause: Clause, uip: Literal) -> usize {
         let second_deepest = clause
             .iter()
             .copied()
             .filter(|&literal| literal != uip)
-            .max_by_key(|&literal| self.trail.assignment[variable_name(literal)].decision_level());
+            .max_by_key(|&literal| self.level_of(literal));
 
-        let back_level = second_deepest.map_or(0, |back| {
-            self.trail.assignment[variable_name(back)].decision_level()
-        });
+        let back_level = second_deepest.map_or(0, |back| self.level_of(back));
 
         debug_assert!(back_level + 1 < self.trail.levels.len());
 
-        self.trail
-            .levels
-            .drain(back_level + 1..)
-            .flatten()
-            .for_each(|(variable, _)| self.trail.assignment[variable] = Unset);
+        let level = self.backtrack_level(back_level);
+        self.backtrack(level);
 
         let new_clause_id = self.formula.len();
 
@@ -134,37 +439,325 @@ impl<'a, D: DecisionHeuristic, C: ConflictAnalysis> CDCL<'a, D, C> {
             self.add_learned_clause(clause, Unit(uip));
         }
 
+        self.notify_backtrack_and_add_clause(level, new_clause_id);
+
+        new_clause_id
+    }
+
+    fn notify_backtrack_and_add_clause(&mut self, level: usize, clause_id: usize) {
         self.decision_heuristic.backtrack_and_add_clause(
-            &self.formula,
+            self.formula,
             &self.trail,
-            back_level,
-            new_clause_id,
+            level,
+            clause_id,
         );
         self.conflict_analysis.backtrack_and_add_clause(
-            &self.formula,
+            self.formula,
             &self.trail,
-            back_level,
-            new_clause_id,
+            level,
+            clause_id,
         );
+    }
 
-        new_clause_id
+    /// Adds a learned clause after the backjump. Returns its literal if the clause is unit. A
+    /// unit clause is only propagated once the search is back at level zero.
+    fn add_extra_clause(&mut self, mut clause: Clause) -> Option<(Literal, usize)> {
+        let back_level = self.trail.levels.len() - 1;
+        let new_clause_id = self.formula.len();
+
+        if let [literal] = clause[..] {
+            self.add_learned_clause(clause, Unit(literal));
+            self.notify_backtrack_and_add_clause(back_level, new_clause_id);
+            self.pending_units.push(new_clause_id);
+
+            return None;
+        }
+
+        // true literals first, then unset ones, then false ones from the deepest level
+        clause.sort_by_key(
+            |&literal| match self.trail.assignment[variable_name(literal)] {
+                Unset => usize::MAX - 1,
+                state if state.bool_value() == (literal >= 0) => usize::MAX,
+                state => state.decision_level(),
+            },
+        );
+        clause.reverse();
+
+        let (a, b) = (clause[0], clause[1]);
+        let is_false = |literal: Literal| match self.trail.assignment[variable_name(literal)] {
+            Unset => false,
+            state => state.bool_value() != (literal >= 0),
+        };
+        let is_unit = self.trail.assignment[variable_name(a)].is_unset() && is_false(b);
+
+        self.add_learned_clause(clause, Watched(a, b));
+        self.trail.add_watch(a, new_clause_id);
+        self.trail.add_watch(b, new_clause_id);
+
+        self.notify_backtrack_and_add_clause(back_level, new_clause_id);
+
+        is_unit.then_some((a, new_clause_id))
+    }
+
+    /// Propagates the unit clauses learned above level zero, once back there. Returns `false` if
+    /// one of them is falsified.
+    fn propagate_pending_units(&mut self) -> bool {
+        if self.trail.levels.len() > 1 {
+            return true;
+        }
+
+        for clause_id in mem::take(&mut self.pending_units) {
+            let literal = self.formula[clause_id][0];
+
+            match self.trail.assignment[variable_name(literal)] {
+                Unset => self.propagate_literal(literal, clause_id),
+                state if state.bool_value() != (literal >= 0) => return false,
+                _ => {}
+            }
+        }
+
+        true
+    }
+
+    fn level_of(&self, literal: Literal) -> usize {
+        self.trail.assignment[variable_name(literal)].decision_level()
     }
 
     fn propagate_literal(&mut self, literal: Literal, reason_id: usize) {
-        self.trail.propagate_literal(literal, reason_id);
+        match self.options.chronological_backtracking {
+            None => self.trail.propagate_literal(literal, reason_id),
+            Some(_) => {
+                // the literal belongs to the deepest level of its reason, which may be below the
+                // current one
+                let level = self.formula[reason_id]
+                    .iter()
+                    .filter(|&&other| other != literal)
+                    .map(|&other| self.level_of(other))
+                    .max()
+                    .unwrap_or(0);
+
+                self.trail.propagate_literal_at(literal, reason_id, level);
+            }
+        }
+
+        self.queue.push_back(variable_name(literal));
         self.decision_heuristic
-            .propagate_literal(&self.formula, &self.trail, literal, reason_id);
+            .propagate_literal(self.formula, &self.trail, literal, reason_id);
         self.conflict_analysis
-            .propagate_literal(&self.formula, &self.trail, literal, reason_id);
+            .propagate_literal(self.formula, &self.trail, literal, reason_id);
+    }
+
+    /// Watches `a` and `b` in the clause instead of its current watches.
+    fn rewatch(&mut self, clause_id: usize, a: Literal, b: Literal) {
+        let (old_a, old_b) = self.trail.clause_types[clause_id].unwrap_watched();
+
+        self.trail.clause_types[clause_id] = Watched(a, b);
+
+        for literal in [a, b] {
+            if literal != old_a && literal != old_b {
+                self.trail.add_watch(literal, clause_id);
+            }
+        }
+    }
+
+    /// Watches the two best literals of the clause: true or unset ones, then false ones from the
+    /// deepest level. Backtracking then never leaves both watches false while another literal of
+    /// the clause is unset, even with literals assigned out of order.
+    fn rewatch_best(&mut self, clause_id: usize) {
+        let rank = |literal: Literal| match self.trail.assignment[variable_name(literal)] {
+            Unset => usize::MAX,
+            state if state.bool_value() == (literal >= 0) => usize::MAX,
+            state => state.decision_level(),
+        };
+
+        let clause = &self.formula[clause_id];
+        let mut best = [clause[0], clause[1]];
+
+        if rank(best[1]) > rank(best[0]) {
+            best.swap(0, 1);
+        }
+
+        for &literal in &clause[2..] {
+            if rank(literal) > rank(best[0]) {
+                best = [literal, best[0]];
+            } else if rank(literal) > rank(best[1]) {
+                best[1] = literal;
+            }
+        }
+
+        self.rewatch(clause_id, best[0], best[1]);
+    }
+
+    /// Handles the falsified clause `clause_id`, found while processing `variable`: learns a
+    /// clause and backjumps. Returns `false` if the formula is unsatisfiable.
+    fn resolve_conflict(&mut self, clause_id: usize, variable: Variable) -> bool {
+        self.statistics.conflicts += 1;
+        self.restart_countdown = self.restart_countdown.saturating_sub(1);
+
+        if self.options.chronological_backtracking.is_none() {
+            if self.trail.levels.len() == 1 {
+                return false;
+            }
+        } else {
+            // with literals assigned out of order the conflict may lie below the current level
+            let conflict_level = self.formula[clause_id]
+                .iter()
+                .map(|&literal| self.level_of(literal))
+                .max()
+                .unwrap();
+
+            if conflict_level == 0 {
+                return false;
+            }
+
+            let mut deepest = self.formula[clause_id]
+                .iter()
+                .copied()
+                .filter(|&literal| self.level_of(literal) == conflict_level);
+            let literal = deepest.next().unwrap();
+
+            if deepest.next().is_none() {
+                // the clause is unit below its deepest level, it becomes the reason of its literal
+                self.backtrack_and_notify(conflict_level - 1);
+                self.requeue(variable);
+                self.propagate_literal(literal, clause_id);
+                self.rewatch_best(clause_id);
+
+                return true;
+            }
+
+            if conflict_level < self.trail.levels.len() - 1 {
+                self.backtrack_and_notify(conflict_level);
+            }
+        }
+
+        let (conflict, extra) = self.conflict_analysis.analyze_conflict_with_extras(
+            self.formula,
+            &self.trail,
+            self.formula[clause_id].clone(),
+        );
+
+        let uip = conflict
+            .iter()
+            .copied()
+            .max_by_key(|&literal| self.level_of(literal))
+            .unwrap();
+
+        let new_clause_id = self.backtrack_and_add_uip_clause(conflict, uip);
+
+        let units: Vec<_> = extra
+            .into_iter()
+            .filter_map(|clause| self.add_extra_clause(clause))
+            .collect();
+
+        self.requeue(variable);
+        self.propagate_literal(uip, new_clause_id);
+
+        for (literal, reason_id) in units {
+            if self.trail.assignment[variable_name(literal)].is_unset() {
+                self.propagate_literal(literal, reason_id);
+            }
+        }
+
+        if self.options.chronological_backtracking.is_some() {
+            self.rewatch_best(clause_id);
+        }
+
+        true
+    }
+
+    fn is_terminated(&self) -> bool {
+        self.terminate
+            .as_ref()
+            .is_some_and(|terminate| terminate.load(Ordering::Relaxed))
     }
 
-    fn process_unit_clauses(&mut self) -> bool {
-        let mut variable_index = 0;
+    fn report_progress(&mut self) {
+        let Some((interval, progress)) = &mut self.progress else {
+            return;
+        };
 
-        'unit_variables: while variable_index < self.trail.levels.last().unwrap().len() {
-            let variable = self.trail.levels.last().unwrap()[variable_index].0;
-            variable_index += 1;
+        if !self.statistics.conflicts.is_multiple_of(*interval) {
+            return;
+        }
+
+        progress.progress(&ProgressEvent {
+            conflicts: self.statistics.conflicts,
+            decisions: self.statistics.decisions,
+            learned_clauses: self.formula.len() - self.original_clauses,
+            trail_size: self.trail.levels.iter().map(Vec::len).sum(),
+            level: self.trail.levels.len() - 1,
+        });
+    }
+
+    /// Adds `clause`, falsified by the current assignment, to exclude it from the search. Instead
+    /// of starting over, the search backjumps: below the deepest level of the clause if only one
+    /// of its literals is there, which is then propagated, otherwise the clause is analyzed as a
+    /// conflict on that level. Returns `false` if no assignment is left.
+    pub fn add_blocking_clause(&mut self, mut clause: Clause) -> bool {
+        debug_assert!(clause
+            .iter()
+            .all(
+                |&literal| !self.trail.assignment[variable_name(literal)].is_unset()
+                    && self.trail.to_literal(variable_name(literal)) == !literal
+            ));
+
+        clause.sort_by_key(|&literal| Reverse(self.level_of(literal)));
+
+        let Some(&deepest) = clause.first() else {
+            return false;
+        };
+
+        let level = self.level_of(deepest);
+
+        if level == 0 {
+            return false;
+        }
+
+        let clause_id = self.formula.len();
+        let second_level = clause.get(1).map(|&literal| self.level_of(literal));
+
+        if second_level == Some(level) {
+            let second = clause[1];
+
+            self.backtrack(level);
+            self.add_learned_clause(clause, Watched(deepest, second));
+            self.trail.add_watch(deepest, clause_id);
+            self.trail.add_watch(second, clause_id);
+            self.notify_backtrack_and_add_clause(level, clause_id);
+
+            return self.resolve_conflict(clause_id, variable_name(deepest));
+        }
 
+        let back_level = second_level.unwrap_or(0);
+
+        self.backtrack(back_level);
+
+        if let Some(&second) = clause.get(1) {
+            self.add_learned_clause(clause, Watched(deepest, second));
+            self.trail.add_watch(deepest, clause_id);
+            self.trail.add_watch(second, clause_id);
+        } else {
+            self.add_learned_clause(clause, Unit(deepest));
+        }
+
+        self.notify_backtrack_and_add_clause(back_level, clause_id);
+        self.propagate_literal(deepest, clause_id);
+
+        true
+    }
+
+    /// Queues `variable` again if it is still assigned, its watches were not all processed.
+    fn requeue(&mut self, variable: Variable) {
+        if !self.trail.assignment[variable].is_unset() {
+            self.queue.push_front(variable);
+        }
+    }
+
+    /// Propagates the queued literals, resolving the conflicts on the way. Returns how the search
+    /// ended if it did.
+    fn process_unit_clauses(&mut self) -> Option<SolveResult> {
+        'unit_variables: while let Some(variable) = self.queue.pop_front() {
             let value = self.trail.assignment[variable].bool_value();
             let falsified_literal = !self.trail.to_literal(variable);
 
@@ -184,39 +777,37 @@ impl<'a, D: DecisionHeuristic, C: ConflictAnalysis> CDCL<'a, D, C> {
                 }
 
                 let other_literal = a ^ b ^ falsified_literal;
+                let chronological = self.options.chronological_backtracking.is_some();
 
                 match self.get_clause_type(&self.formula[clause_id], Some(other_literal)) {
                     Unwatched => {
                         unreachable!()
                     }
-                    Satisfied => {}
+                    Satisfied => {
+                        if chronological {
+                            self.rewatch_best(clause_id);
+                        }
+                    }
                     Falsified => {
-                        if self.trail.levels.len() == 1 {
-                            return false;
+                        if !self.resolve_conflict(clause_id, variable) {
+                            return Some(SolveResult::Unsatisfiable);
                         }
 
-                        let conflict = self.conflict_analysis.analyze_conflict(
-                            &self.formula,
-                            &self.trail,
-                            self.formula[clause_id].clone(),
-                        );
-
-                        let uip = conflict
-                            .iter()
-                            .copied()
-                            .max_by_key(|literal| {
-                                self.trail.assignment[variable_name(*literal)].decision_level()
-                            })
-                            .unwrap();
-
-                        let new_clause_id = self.backtrack_and_add_uip_clause(conflict, uip);
+                        self.report_progress();
 
-                        variable_index = self.trail.levels.last().unwrap().len();
-                        self.propagate_literal(uip, new_clause_id);
+                        if self.is_terminated() {
+                            return Some(SolveResult::Interrupted);
+                        }
 
                         continue 'unit_variables;
                     }
-                    Unit(new_literal) => self.propagate_literal(new_literal, clause_id),
+                    Unit(new_literal) => {
+                        self.propagate_literal(new_literal, clause_id);
+
+                        if chronological {
+                            self.rewatch_best(clause_id);
+                        }
+                    }
                     Watched(a, b) => {
                         self.trail.clause_types[clause_id] = Watched(a, b);
                         if a != falsified_literal {
@@ -228,30 +819,59 @@ impl<'a, D: DecisionHeuristic, C: ConflictAnalysis> CDCL<'a, D, C> {
             }
         }
 
-        true
+        None
     }
 
+    /// Whether the formula is satisfiable. Panics if the search is interrupted, see
+    /// [`search`](Self::search).
     pub fn solve(&mut self) -> bool {
-        if !self.preprocess_clauses() {
-            return false;
+        match self.search() {
+            SolveResult::Satisfiable => true,
+            SolveResult::Unsatisfiable => false,
+            SolveResult::Interrupted => panic!("Search interrupted, use `search` to handle it"),
+        }
+    }
+
+    /// Searches for a model. After one is found, the search can go on for another once the
+    /// model is excluded with [`add_blocking_clause`](Self::add_blocking_clause).
+    pub fn search(&mut self) -> SolveResult {
+        if !mem::replace(&mut self.started, true) && !self.preprocess_clauses() {
+            return SolveResult::Unsatisfiable;
         }
 
         loop {
-            if !self.process_unit_clauses() {
-                return false;
+            if let Some(result) = self.process_unit_clauses() {
+                return result;
+            }
+
+            if !self.propagate_pending_units() || !self.simplify_if_due() {
+                return SolveResult::Unsatisfiable;
+            }
+
+            if !self.queue.is_empty() {
+                // learned or simplified unit clauses
+                continue;
+            }
+
+            self.restart_if_due();
+
+            if self.is_terminated() {
+                return SolveResult::Interrupted;
             }
 
             match self
                 .decision_heuristic
-                .decide_literal(&self.formula, &self.trail)
+                .decide_literal(self.formula, &self.trail)
             {
                 None => {
-                    return true;
+                    return SolveResult::Satisfiable;
                 }
                 Some(literal) => {
+                    self.statistics.decisions += 1;
                     self.trail.decide_literal(literal);
+                    self.queue.push_back(variable_name(literal));
                     self.conflict_analysis
-                        .decide_literal(&self.formula, &self.trail, literal);
+                        .decide_literal(self.formula, &self.trail, literal);
                 }
             }
         }
@@ -268,6 +888,9 @@ pub trait DecisionHeuristic {
         level: usize,
         clause_id: usize,
     );
+    /// Called when the solver backtracks to `level` without learning a clause, see
+    /// [`ConflictAnalysis::backtrack`].
+    fn backtrack(&mut self, _formula: &Formula, _trail: &Trail, _level: usize) {}
     fn propagate_literal(
         &mut self,
         formula: &Formula,
@@ -276,12 +899,104 @@ pub trait DecisionHeuristic {
         reason_id: usize,
     );
     fn decide_literal(&mut self, formula: &Formula, trail: &Trail) -> Option<Literal>;
+    /// Whether `a` would be decided before `b` if both were unassigned. Restarts keep the levels
+    /// decided before the best unassigned variable, so the default keeps none of them.
+    fn decides_before(&self, _a: Variable, _b: Variable) -> bool {
+        false
+    }
 }
 
 pub trait ConflictAnalysis {
     fn from_formula(n: usize, formula: &Formula) -> Self;
 
     fn analyze_conflict(&mut self, formula: &Formula, trail: &Trail, conflict: Clause) -> Clause;
+    /// Returns the asserting clause together with extra, not necessarily asserting, learned clauses.
+    fn analyze_conflict_with_extras(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        conflict: Clause,
+    ) -> (Clause, Vec<Clause>) {
+        (self.analyze_conflict(formula, trail, conflict), Vec::new())
+    }
+    /// The implication graph of the conflict just analyzed into `learned`, called before the
+    /// backjump.
+    fn implication_graph(
+        &self,
+        formula: &Formula,
+        trail: &Trail,
+        conflict: &Clause,
+        learned: &Clause,
+    ) -> ImplicationGraph {
+        ImplicationGraph::from_trail(formula, trail, conflict, learned)
+    }
+    /// Called after backtracking to `level` and adding the learned clause `clause_id`. With
+    /// chronological backtracking, literals assigned after unassigned ones may stay assigned.
+    fn backtrack_and_add_clause(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        level: usize,
+        clause_id: usize,
+    );
+    /// Called when the solver backtracks to `level` without learning a clause: on restarts, and
+    /// with chronological backtracking to the level of a conflict below the current one, or below
+    /// a conflict clause that is unit there.
+    fn backtrack(&mut self, _formula: &Formula, _trail: &Trail, _level: usize) {}
+    /// Called at level zero after the clauses satisfied there were unwatched and the literals
+    /// false there removed from the others. Clause ids are unchanged.
+    fn simplify(&mut self, _formula: &Formula, _trail: &Trail) {}
+    /// Counters of the analysis worth reporting after the search, with their names.
+    fn named_statistics(&self) -> Vec<(&'static str, usize)> {
+        Vec::new()
+    }
+    fn propagate_literal(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        literal: Literal,
+        reason_id: usize,
+    );
+    fn decide_literal(&mut self, formula: &Formula, trail: &Trail, literal: Literal);
+}
+
+/// Object-safe counterpart of [`DecisionHeuristic`], built by a `DecisionHeuristicFactory`.
+pub trait DynDecisionHeuristic {
+    fn backtrack_and_add_clause(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        level: usize,
+        clause_id: usize,
+    );
+    fn backtrack(&mut self, formula: &Formula, trail: &Trail, level: usize);
+    fn propagate_literal(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        literal: Literal,
+        reason_id: usize,
+    );
+    fn decide_literal(&mut self, formula: &Formula, trail: &Trail) -> Option<Literal>;
+    fn decides_before(&self, a: Variable, b: Variable) -> bool;
+}
+
+/// Object-safe counterpart of [`ConflictAnalysis`], built by a `ConflictAnalysisFactory`.
+pub trait DynConflictAnalysis {
+    fn analyze_conflict(&mut self, formula: &Formula, trail: &Trail, conflict: Clause) -> Clause;
+    fn analyze_conflict_with_extras(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        conflict: Clause,
+    ) -> (Clause, Vec<Clause>);
+    fn implication_graph(
+        &self,
+        formula: &Formula,
+        trail: &Trail,
+        conflict: &Clause,
+        learned: &Clause,
+    ) -> ImplicationGraph;
     fn backtrack_and_add_clause(
         &mut self,
         formula: &Formula,
@@ -289,6 +1004,9 @@ pub trait ConflictAnalysis {
         level: usize,
         clause_id: usize,
     );
+    fn backtrack(&mut self, formula: &Formula, trail: &Trail, level: usize);
+    fn simplify(&mut self, formula: &Formula, trail: &Trail);
+    fn named_statistics(&self) -> Vec<(&'static str, usize)>;
     fn propagate_literal(
         &mut self,
         formula: &Formula,
@@ -298,3 +1016,193 @@ pub trait ConflictAnalysis {
     );
     fn decide_literal(&mut self, formula: &Formula, trail: &Trail, literal: Literal);
 }
+
+impl<D: DecisionHeuristic> DynDecisionHeuristic for D {
+    fn backtrack_and_add_clause(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        level: usize,
+        clause_id: usize,
+    ) {
+        DecisionHeuristic::backtrack_and_add_clause(self, formula, trail, level, clause_id)
+    }
+
+    fn backtrack(&mut self, formula: &Formula, trail: &Trail, level: usize) {
+        DecisionHeuristic::backtrack(self, formula, trail, level)
+    }
+
+    fn propagate_literal(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        literal: Literal,
+        reason_id: usize,
+    ) {
+        DecisionHeuristic::propagate_literal(self, formula, trail, literal, reason_id)
+    }
+
+    fn decide_literal(&mut self, formula: &Formula, trail: &Trail) -> Option<Literal> {
+        DecisionHeuristic::decide_literal(self, formula, trail)
+    }
+
+    fn decides_before(&self, a: Variable, b: Variable) -> bool {
+        DecisionHeuristic::decides_before(self, a, b)
+    }
+}
+
+impl<C: ConflictAnalysis> DynConflictAnalysis for C {
+    fn analyze_conflict(&mut self, formula: &Formula, trail: &Trail, conflict: Clause) -> Clause {
+        ConflictAnalysis::analyze_conflict(self, formula, trail, conflict)
+    }
+
+    fn analyze_conflict_with_extras(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        conflict: Clause,
+    ) -> (Clause, Vec<Clause>) {
+        ConflictAnalysis::analyze_conflict_with_extras(self, formula, trail, conflict)
+    }
+
+    fn implication_graph(
+        &self,
+        formula: &Formula,
+        trail: &Trail,
+        conflict: &Clause,
+        learned: &Clause,
+    ) -> ImplicationGraph {
+        ConflictAnalysis::implication_graph(self, formula, trail, conflict, learned)
+    }
+
+    fn backtrack_and_add_clause(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        level: usize,
+        clause_id: usize,
+    ) {
+        ConflictAnalysis::backtrack_and_add_clause(self, formula, trail, level, clause_id)
+    }
+
+    fn backtrack(&mut self, formula: &Formula, trail: &Trail, level: usize) {
+        ConflictAnalysis::backtrack(self, formula, trail, level)
+    }
+
+    fn simplify(&mut self, formula: &Formula, trail: &Trail) {
+        ConflictAnalysis::simplify(self, formula, trail)
+    }
+
+    fn named_statistics(&self) -> Vec<(&'static str, usize)> {
+        ConflictAnalysis::named_statistics(self)
+    }
+
+    fn propagate_literal(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        literal: Literal,
+        reason_id: usize,
+    ) {
+        ConflictAnalysis::propagate_literal(self, formula, trail, literal, reason_id)
+    }
+
+    fn decide_literal(&mut self, formula: &Formula, trail: &Trail, literal: Literal) {
+        ConflictAnalysis::decide_literal(self, formula, trail, literal)
+    }
+}
+
+impl DynDecisionHeuristic for Box<dyn DynDecisionHeuristic> {
+    fn backtrack_and_add_clause(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        level: usize,
+        clause_id: usize,
+    ) {
+        (**self).backtrack_and_add_clause(formula, trail, level, clause_id)
+    }
+
+    fn backtrack(&mut self, formula: &Formula, trail: &Trail, level: usize) {
+        (**self).backtrack(formula, trail, level)
+    }
+
+    fn propagate_literal(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        literal: Literal,
+        reason_id: usize,
+    ) {
+        (**self).propagate_literal(formula, trail, literal, reason_id)
+    }
+
+    fn decide_literal(&mut self, formula: &Formula, trail: &Trail) -> Option<Literal> {
+        (**self).decide_literal(formula, trail)
+    }
+
+    fn decides_before(&self, a: Variable, b: Variable) -> bool {
+        (**self).decides_before(a, b)
+    }
+}
+
+impl DynConflictAnalysis for Box<dyn DynConflictAnalysis> {
+    fn analyze_conflict(&mut self, formula: &Formula, trail: &Trail, conflict: Clause) -> Clause {
+        (**self).analyze_conflict(formula, trail, conflict)
+    }
+
+    fn analyze_conflict_with_extras(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        conflict: Clause,
+    ) -> (Clause, Vec<Clause>) {
+        (**self).analyze_conflict_with_extras(formula, trail, conflict)
+    }
+
+    fn implication_graph(
+        &self,
+        formula: &Formula,
+        trail: &Trail,
+        conflict: &Clause,
+        learned: &Clause,
+    ) -> ImplicationGraph {
+        (**self).implication_graph(formula, trail, conflict, learned)
+    }
+
+    fn backtrack_and_add_clause(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        level: usize,
+        clause_id: usize,
+    ) {
+        (**self).backtrack_and_add_clause(formula, trail, level, clause_id)
+    }
+
+    fn backtrack(&mut self, formula: &Formula, trail: &Trail, level: usize) {
+        (**self).backtrack(formula, trail, level)
+    }
+
+    fn simplify(&mut self, formula: &Formula, trail: &Trail) {
+        (**self).simplify(formula, trail)
+    }
+
+    fn named_statistics(&self) -> Vec<(&'static str, usize)> {
+        (**self).named_statistics()
+    }
+
+    fn propagate_literal(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        literal: Literal,
+        reason_id: usize,
+    ) {
+        (**self).propagate_literal(formula, trail, literal, reason_id)
+    }
+
+    fn decide_literal(&mut self, formula: &Formula, trail: &Trail, literal: Literal) {
+        (**self).decide_literal(formula, trail, literal)
+    }
+}
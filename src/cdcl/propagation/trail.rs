@@ -48,25 +48,16 @@ impl VariableState {
     }
 
     pub fn is_true(&self) -> bool {
-        match self {
-            True(_) => true,
-            _ => false,
-        }
+        matches!(self, True(_))
     }
 
     #[allow(dead_code)]
     pub fn is_false(&self) -> bool {
-        match self {
-            False(_) => true,
-            _ => false,
-        }
+        matches!(self, False(_))
     }
 
     pub fn is_unset(&self) -> bool {
-        match self {
-            Unset => true,
-            _ => false,
-        }
+        matches!(self, Unset)
     }
 }
 
@@ -96,6 +87,9 @@ impl ClauseType {
 #[derive(Debug, Default, Clone)]
 pub struct Trail {
     pub assignment: Vec<VariableState>,
+    /// The assigned variables of each level, the decision first. Every literal follows the
+    /// literals of its level in its reason; with chronological backtracking a level may gain
+    /// literals while a higher one is open.
     pub levels: Vec<Vec<(Variable, Reason)>>,
     pub clause_types: Vec<ClauseType>,
     pub watches: Vec<[Vec<usize>; 2]>,
@@ -138,10 +132,37 @@ impl Trail {
         self.propagate_variable(variable_name(literal), literal >= 0, reason_id)
     }
 
+    /// Propagates `literal` on `level`, below the current level if its reason is.
+    pub fn propagate_literal_at(&mut self, literal: Literal, reason_id: usize, level: usize) {
+        let variable = variable_name(literal);
+
+        self.assign_variable(variable, literal >= 0, level);
+        self.levels[level].push((variable, Some(reason_id)));
+    }
+
+    /// Unassigns the levels above `level`.
+    pub fn backtrack(&mut self, level: usize) {
+        for (variable, _) in self.levels.drain(level + 1..).flatten() {
+            self.assignment[variable] = Unset;
+        }
+    }
+
     pub fn add_watch(&mut self, literal: Literal, clause_id: usize) {
         self.watches[variable_name(literal)][(literal >= 0) as usize].push(clause_id);
     }
 
+    pub fn lbd(&self, clause: &Clause) -> usize {
+        let mut levels: Vec<_> = clause
+            .iter()
+            .map(|literal| self.assignment[variable_name(*literal)].decision_level())
+            .collect();
+
+        levels.sort_unstable();
+        levels.dedup();
+
+        levels.len()
+    }
+
     pub fn to_literal(&self, variable: Variable) -> Literal {
         let literal = variable as Literal;
 
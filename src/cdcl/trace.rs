@@ -0,0 +1,218 @@
+use std::fmt::Write;
+
+use crate::cdcl::implication_graph::{dimacs_literal, ImplicationGraph};
+use crate::cdcl::propagation::{DynConflictAnalysis, Trail};
+use crate::cdcl::{variable_name, Clause, Formula, Literal};
+
+/// A clause some learning scheme produced at a conflict.
+#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct TracedClause {
+    pub scheme: String,
+    pub clause: Clause,
+    pub lbd: usize,
+    /// Level the solver would backjump to with the clause.
+    pub backjump_level: usize,
+}
+
+impl TracedClause {
+    pub fn new(scheme: &str, clause: Clause, trail: &Trail) -> Self {
+        Self {
+            scheme: scheme.to_string(),
+            lbd: trail.lbd(&clause),
+            backjump_level: backjump_level(&clause, trail),
+            clause,
+        }
+    }
+}
+
+/// The clauses of every traced scheme at one conflict, the active analysis first.
+#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct ConflictTrace {
+    /// Number of the conflict, counted from one.
+    pub conflict: usize,
+    pub level: usize,
+    pub clauses: Vec<TracedClause>,
+}
+
+impl ConflictTrace {
+    /// Header of the lines written by [`to_lines`](Self::to_lines).
+    pub const HEADER: &'static str = "conflict\tlevel\tscheme\tsize\tlbd\tbackjump\tclause\n";
+
+    /// One tab-separated line per clause, the clause in DIMACS numbering ending with 0.
+    pub fn to_lines(&self) -> String {
+        let mut lines = String::new();
+
+        for traced in &self.clauses {
+            write!(
+                lines,
+                "{}\t{}\t{}\t{}\t{}\t{}\t",
+                self.conflict,
+                self.level,
+                traced.scheme,
+                traced.clause.len(),
+                traced.lbd,
+                traced.backjump_level
+            )
+            .unwrap();
+
+            for &literal in &traced.clause {
+                write!(lines, "{} ", dimacs_literal(literal)).unwrap();
+            }
+
+            lines.push_str("0\n");
+        }
+
+        lines
+    }
+}
+
+/// The deepest level of the clause below the conflict level, 0 for a unit clause.
+pub fn backjump_level(clause: &Clause, trail: &Trail) -> usize {
+    let conflict_level = trail.levels.len() - 1;
+
+    clause
+        .iter()
+        .map(|literal| trail.assignment[variable_name(*literal)].decision_level())
+        .filter(|&level| level != conflict_level)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Runs `analysis` and, on the same trail, the traced `schemes`, passing the clauses of all of
+/// them at every conflict to `trace`. Only the clause of `analysis` is learned, the schemes are
+/// told about it as if they had produced it.
+pub struct TraceConflicts<C: DynConflictAnalysis> {
+    /// Scheme name of `analysis` in the traces.
+    name: String,
+    analysis: C,
+    schemes: Vec<(String, Box<dyn DynConflictAnalysis>)>,
+    conflicts: usize,
+    trace: Box<dyn FnMut(&ConflictTrace)>,
+}
+
+impl<C: DynConflictAnalysis> TraceConflicts<C> {
+    pub fn new(
+        name: &str,
+        analysis: C,
+        schemes: Vec<(String, Box<dyn DynConflictAnalysis>)>,
+        trace: impl FnMut(&ConflictTrace) + 'static,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            analysis,
+            schemes,
+            conflicts: 0,
+            trace: Box::new(trace),
+        }
+    }
+
+    pub fn analysis(&self) -> &C {
+        &self.analysis
+    }
+}
+
+impl<C: DynConflictAnalysis> DynConflictAnalysis for TraceConflicts<C> {
+    fn analyze_conflict(&mut self, formula: &Formula, trail: &Trail, conflict: Clause) -> Clause {
+        self.analyze_conflict_with_extras(formula, trail, conflict)
+            .0
+    }
+
+    fn analyze_conflict_with_extras(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        conflict: Clause,
+    ) -> (Clause, Vec<Clause>) {
+        self.conflicts += 1;
+
+        let (learned, extra) =
+            self.analysis
+                .analyze_conflict_with_extras(formula, trail, conflict.clone());
+
+        let mut clauses = vec![TracedClause::new(&self.name, learned.clone(), trail)];
+
+        for (scheme, analysis) in &mut self.schemes {
+            let clause = analysis.analyze_conflict(formula, trail, conflict.clone());
+
+            clauses.push(TracedClause::new(scheme, clause, trail));
+        }
+
+        (self.trace)(&ConflictTrace {
+            conflict: self.conflicts,
+            level: trail.levels.len() - 1,
+            clauses,
+        });
+
+        (learned, extra)
+    }
+
+    fn implication_graph(
+        &self,
+        formula: &Formula,
+        trail: &Trail,
+        conflict: &Clause,
+        learned: &Clause,
+    ) -> ImplicationGraph {
+        self.analysis
+            .implication_graph(formula, trail, conflict, learned)
+    }
+
+    fn backtrack_and_add_clause(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        level: usize,
+        clause_id: usize,
+    ) {
+        self.analysis
+            .backtrack_and_add_clause(formula, trail, level, clause_id);
+
+        for (_, analysis) in &mut self.schemes {
+            analysis.backtrack_and_add_clause(formula, trail, level, clause_id);
+        }
+    }
+
+    fn backtrack(&mut self, formula: &Formula, trail: &Trail, level: usize) {
+        self.analysis.backtrack(formula, trail, level);
+
+        for (_, analysis) in &mut self.schemes {
+            analysis.backtrack(formula, trail, level);
+        }
+    }
+
+    fn simplify(&mut self, formula: &Formula, trail: &Trail) {
+        self.analysis.simplify(formula, trail);
+
+        for (_, analysis) in &mut self.schemes {
+            analysis.simplify(formula, trail);
+        }
+    }
+
+    /// Those of the active analysis only.
+    fn named_statistics(&self) -> Vec<(&'static str, usize)> {
+        self.analysis.named_statistics()
+    }
+
+    fn propagate_literal(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        literal: Literal,
+        reason_id: usize,
+    ) {
+        self.analysis
+            .propagate_literal(formula, trail, literal, reason_id);
+
+        for (_, analysis) in &mut self.schemes {
+            analysis.propagate_literal(formula, trail, literal, reason_id);
+        }
+    }
+
+    fn decide_literal(&mut self, formula: &Formula, trail: &Trail, literal: Literal) {
+        self.analysis.decide_literal(formula, trail, literal);
+
+        for (_, analysis) in &mut self.schemes {
+            analysis.decide_literal(formula, trail, literal);
+        }
+    }
+}
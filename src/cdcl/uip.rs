@@ -0,0 +1,249 @@
+use crate::cdcl::first_uip::FirstUIP;
+use crate::cdcl::propagation::{ConflictAnalysis, Trail};
+use crate::cdcl::{variable_name, Clause, Formula, Literal};
+
+/// Resolves the literals of `clause` on `level` with their reasons, walking the trail backwards,
+/// until a single one remains. Returns `false` and leaves `clause` untouched if `allowed_levels`
+/// is set and a resolution would bring in a literal on another level, except level zero.
+fn resolve_level(
+    formula: &Formula,
+    trail: &Trail,
+    in_clause: &mut [bool],
+    clause: &mut Clause,
+    level: usize,
+    allowed_levels: Option<&[bool]>,
+) -> bool {
+    let level_of = |literal: Literal| trail.assignment[variable_name(literal)].decision_level();
+
+    let mut pending = clause
+        .iter()
+        .filter(|&&literal| level_of(literal) == level)
+        .count();
+
+    if pending < 2 {
+        return true;
+    }
+
+    let mut result = clause.clone();
+    let mut added = vec![];
+    let mut removed = vec![];
+
+    for &(variable, reason) in trail.levels[level].iter().rev() {
+        if !in_clause[variable] {
+            continue;
+        }
+
+        if pending == 1 {
+            break;
+        }
+
+        let reason = &formula[reason.expect("decision is the last UIP of its level")];
+
+        if let Some(allowed) = allowed_levels {
+            if reason.iter().any(|&literal| {
+                let level = level_of(literal);
+
+                !in_clause[variable_name(literal)] && level != 0 && !allowed[level]
+            }) {
+                for variable in removed {
+                    in_clause[variable] = true;
+                }
+                for variable in added {
+                    in_clause[variable] = false;
+                }
+
+                return false;
+            }
+        }
+
+        in_clause[variable] = false;
+        removed.push(variable);
+        result.retain(|&literal| variable_name(literal) != variable);
+        pending -= 1;
+
+        for literal in reason.iter().copied() {
+            let other = variable_name(literal);
+
+            if other != variable && !in_clause[other] {
+                in_clause[other] = true;
+                added.push(other);
+                result.push(literal);
+
+                if level_of(literal) == level {
+                    pending += 1;
+                }
+            }
+        }
+    }
+
+    *clause = result;
+
+    true
+}
+
+fn levels_below_conflict(trail: &Trail, clause: &Clause) -> Vec<usize> {
+    let mut levels: Vec<_> = clause
+        .iter()
+        .map(|literal| trail.assignment[variable_name(*literal)].decision_level())
+        .collect();
+
+    levels.sort_unstable();
+    levels.dedup();
+    levels.pop();
+
+    levels
+}
+
+/// Classical k-UIP learning: starting from the first UIP clause, the literals of each of the
+/// `k - 1` levels right below the conflict level are replaced by the UIP of their level.
+#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct KthUIP {
+    first_uip: FirstUIP,
+    in_clause: Vec<bool>,
+    k: usize,
+}
+
+impl KthUIP {
+    pub const ALL: usize = usize::MAX;
+
+    pub fn new(n: usize, k: usize) -> Self {
+        assert_ne!(k, 0);
+
+        Self {
+            first_uip: FirstUIP::new(n),
+            in_clause: vec![false; n],
+            k,
+        }
+    }
+}
+
+impl ConflictAnalysis for KthUIP {
+    /// Defaults to the second UIP.
+    fn from_formula(n: usize, _formula: &Formula) -> Self {
+        Self::new(n, 2)
+    }
+
+    fn analyze_conflict(&mut self, formula: &Formula, trail: &Trail, conflict: Clause) -> Clause {
+        let mut clause = self.first_uip.analyze_conflict(formula, trail, conflict);
+        let conflict_level = trail.levels.len() - 1;
+
+        for literal in clause.iter() {
+            self.in_clause[variable_name(*literal)] = true;
+        }
+
+        for level in (1..conflict_level).rev() {
+            if conflict_level - level >= self.k {
+                break;
+            }
+
+            resolve_level(
+                formula,
+                trail,
+                &mut self.in_clause,
+                &mut clause,
+                level,
+                None,
+            );
+        }
+
+        for literal in clause.iter() {
+            self.in_clause[variable_name(*literal)] = false;
+        }
+
+        clause
+    }
+
+    fn backtrack_and_add_clause(
+        &mut self,
+        _formula: &Formula,
+        _trail: &Trail,
+        _level: usize,
+        _clause_id: usize,
+    ) {
+    }
+
+    fn propagate_literal(
+        &mut self,
+        _formula: &Formula,
+        _trail: &Trail,
+        _literal: Literal,
+        _reason_id: usize,
+    ) {
+    }
+
+    fn decide_literal(&mut self, _formula: &Formula, _trail: &Trail, _literal: Literal) {}
+}
+
+/// All-UIP learning of Feng & Bacchus: starting from the first UIP clause, the literals of each
+/// level are replaced by the UIP of their level unless that brings in a new level.
+#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct AllUIP {
+    first_uip: FirstUIP,
+    in_clause: Vec<bool>,
+    levels: Vec<bool>,
+}
+
+impl AllUIP {
+    pub fn new(n: usize) -> Self {
+        Self {
+            first_uip: FirstUIP::new(n),
+            in_clause: vec![false; n],
+            levels: vec![false; n + 1],
+        }
+    }
+}
+
+impl ConflictAnalysis for AllUIP {
+    fn from_formula(n: usize, _formula: &Formula) -> Self {
+        Self::new(n)
+    }
+
+    fn analyze_conflict(&mut self, formula: &Formula, trail: &Trail, conflict: Clause) -> Clause {
+        let mut clause = self.first_uip.analyze_conflict(formula, trail, conflict);
+
+        for literal in clause.iter() {
+            self.in_clause[variable_name(*literal)] = true;
+            self.levels[trail.assignment[variable_name(*literal)].decision_level()] = true;
+        }
+
+        for level in levels_below_conflict(trail, &clause).into_iter().rev() {
+            if level != 0 {
+                resolve_level(
+                    formula,
+                    trail,
+                    &mut self.in_clause,
+                    &mut clause,
+                    level,
+                    Some(&self.levels),
+                );
+            }
+        }
+
+        for literal in clause.iter() {
+            self.in_clause[variable_name(*literal)] = false;
+            self.levels[trail.assignment[variable_name(*literal)].decision_level()] = false;
+        }
+
+        clause
+    }
+
+    fn backtrack_and_add_clause(
+        &mut self,
+        _formula: &Formula,
+        _trail: &Trail,
+        _level: usize,
+        _clause_id: usize,
+    ) {
+    }
+
+    fn propagate_literal(
+        &mut self,
+        _formula: &Formula,
+        _trail: &Trail,
+        _literal: Literal,
+        _reason_id: usize,
+    ) {
+    }
+
+    fn decide_literal(&mut self, _formula: &Formula, _trail: &Trail, _literal: Literal) {}
+}
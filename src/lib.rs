@@ -1,4 +1,4 @@
 pub mod cdcl;
 pub mod simple_cdcl;
 
-pub(crate) mod sk1flow;
+pub mod sk1flow;
@@ -1,13 +1,32 @@
 use self::Solver::*;
 use ail_project::cdcl;
 use ail_project::cdcl::decision::DecideFirstVariable;
+use ail_project::cdcl::dynamic::*;
+use ail_project::cdcl::enumerate::{Blocking, ModelEnumerator};
 use ail_project::cdcl::first_uip::FirstUIP;
-use ail_project::cdcl::mincut::*;
-use ail_project::cdcl::propagation::ConflictAnalysis;
-use ail_project::cdcl::Formula;
-use clap::Parser;
+use ail_project::cdcl::hybrid::HybridMinCut;
+use ail_project::cdcl::implication_graph::{
+    dimacs_literal, DumpGraph, GraphFormat, ImplicationGraph,
+};
+use ail_project::cdcl::mincut::functions::*;
+use ail_project::cdcl::mincut::heuristic::*;
+use ail_project::cdcl::mincut::level_weight::LevelWeight;
+use ail_project::cdcl::propagation::{ConflictAnalysis, DynConflictAnalysis};
+use ail_project::cdcl::trace::{ConflictTrace, TraceConflicts};
+use ail_project::cdcl::uip;
+use ail_project::cdcl::{Formula, Literal, SolveResult, SolverOptions, CDCL};
+use ail_project::sk1flow::{Dinic, MaxFlow, PushRelabel, SK1Flow};
+use clap::{Parser, ValueEnum};
 use clio::*;
-use std::io::{BufReader, Write};
+use num::BigRational;
+use signal_hook::consts::{SIGINT, SIGTERM};
+use signal_hook::flag;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
 use std::time::SystemTime;
 
 #[derive(clap::ValueEnum, Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
@@ -19,8 +38,52 @@ enum Solver {
     ThirdUIP,
     AllUIP,
     SaturatingAllUIP,
+    KthUIP,
+    TrailKthUIP,
+    TrailAllUIP,
     RelSat,
     MinCut,
+    Lbd,
+    Activity,
+    RealActivity,
+    Hybrid,
+}
+
+#[derive(clap::ValueEnum, Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+enum Side {
+    #[default]
+    Sink,
+    Source,
+}
+
+#[derive(clap::ValueEnum, Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+enum Backend {
+    #[default]
+    Sk1,
+    Dinic,
+    PushRelabel,
+}
+
+#[derive(clap::ValueEnum, Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+enum Weights {
+    #[default]
+    Integer,
+    Real,
+    Rational,
+}
+
+#[derive(clap::ValueEnum, Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+enum Format {
+    #[default]
+    Dot,
+    Json,
+}
+
+#[derive(clap::ValueEnum, Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+enum Block {
+    #[default]
+    Model,
+    Decisions,
 }
 
 #[derive(Parser)]
@@ -35,35 +98,352 @@ struct Opt {
 
     #[clap(long, short, default_value_t, value_enum)]
     solver: Solver,
+
+    /// Minimum cut turned into the learned clause in min-cut solvers
+    #[clap(long, default_value_t, value_enum)]
+    cut_side: Side,
+
+    /// Maximum flow algorithm of the min-cut solvers
+    #[clap(long, default_value_t, value_enum)]
+    flow_backend: Backend,
+
+    /// Also learn the other-side and the first UIP clauses in min-cut solvers
+    #[clap(long)]
+    extra_clauses: bool,
+
+    /// Verify every flow of the min-cut solvers, aborting on a wrong one. Slow: each check
+    /// copies the whole flow network, a cost linear in the implication graph per conflict
+    #[clap(long)]
+    paranoid: bool,
+
+    /// UIP depth of the kth-uip solvers, a number or 'all'
+    #[clap(long, default_value = "1", value_parser = parse_uip_depth)]
+    uip_depth: usize,
+
+    /// Bits of weight lost per level in the UIP solvers, at least 1
+    #[clap(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    weight_decay: u32,
+
+    /// Number type of the weights of the UIP solvers
+    #[clap(long, default_value_t, value_enum)]
+    uip_weights: Weights,
+
+    /// Run the min-cut analysis of the hybrid solver on every k-th conflict only
+    #[clap(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    sample_period: u64,
+
+    /// Backtrack chronologically when a backjump would skip more than this many levels
+    #[clap(long)]
+    chronological_backtracking: Option<usize>,
+
+    /// Stop watching the clauses satisfied and remove the literals falsified at level zero when
+    /// back there
+    #[clap(long)]
+    simplify: bool,
+
+    /// Export the implication graph of the n-th conflict, counted from one
+    #[clap(long, value_parser = clap::value_parser!(u64).range(1..))]
+    dump_graph: Option<u64>,
+
+    /// Format of the exported implication graph
+    #[clap(long, default_value_t, value_enum)]
+    graph_format: Format,
+
+    /// File the implication graph is written to, stderr if not given, away from the solver output
+    #[clap(long)]
+    graph_output: Option<PathBuf>,
+
+    /// Write the clauses of every solver at each conflict to this file, built with the same options
+    #[clap(long)]
+    trace: Option<PathBuf>,
+
+    /// Print every model, projected onto the variables of the 'c ind' lines if there are any
+    #[clap(long)]
+    all_models: bool,
+
+    /// Clause excluding each model found with --all-models
+    #[clap(long, default_value_t, value_enum)]
+    blocking: Block,
 }
 
-fn get_solver<C: ConflictAnalysis + 'static>(
-) -> Box<dyn FnOnce(usize, &mut Formula) -> Option<Vec<bool>>> {
+fn parse_uip_depth(depth: &str) -> std::result::Result<usize, String> {
+    if depth == "all" {
+        return Ok(KthUIPFunction::ALL);
+    }
+
+    match depth.parse() {
+        Ok(0) => Err("UIP depth must be positive".to_string()),
+        Ok(k) => Ok(k),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn get_solver<C: ConflictAnalysis + 'static>() -> Box<dyn ConflictAnalysisFactory> {
     #[cfg(debug_assertions)]
     eprintln!("Running: {}", std::any::type_name::<C>());
 
-    Box::new(cdcl::cdcl_solve::<DecideFirstVariable, C>)
+    conflict_analysis_factory::<C>()
+}
+
+fn get_cut_solver<W: MinCutWeight + 'static, T: WeightHeuristic<W> + 'static>(
+    opt: &Opt,
+    weight_heuristic: impl Fn(usize, &Formula) -> T + 'static,
+) -> Box<dyn ConflictAnalysisFactory> {
+    match opt.flow_backend {
+        Backend::Sk1 => get_cut_solver_with::<W, T, SK1Flow<W>>(opt, weight_heuristic),
+        Backend::Dinic => get_cut_solver_with::<W, T, Dinic<W>>(opt, weight_heuristic),
+        Backend::PushRelabel => get_cut_solver_with::<W, T, PushRelabel<W>>(opt, weight_heuristic),
+    }
+}
+
+fn get_cut_solver_with<
+    W: MinCutWeight + 'static,
+    T: WeightHeuristic<W> + 'static,
+    F: MaxFlow<W> + 'static,
+>(
+    opt: &Opt,
+    weight_heuristic: impl Fn(usize, &Formula) -> T + 'static,
+) -> Box<dyn ConflictAnalysisFactory> {
+    let extra_clauses = opt.extra_clauses;
+    let paranoid = opt.paranoid;
+    let cut_side = match opt.cut_side {
+        Side::Sink => CutSide::Sink,
+        Side::Source => CutSide::Source,
+    };
+
+    #[cfg(debug_assertions)]
+    eprintln!(
+        "Running: {}",
+        std::any::type_name::<MinCutConflict<W, T, F>>()
+    );
+
+    Box::new(
+        move |n: usize, formula: &Formula| -> Box<dyn DynConflictAnalysis> {
+            Box::new(
+                MinCutConflict::<W, T, F>::from_formula(n, formula)
+                    .with_weight_heuristic(weight_heuristic(n, formula))
+                    .with_extra_clauses(extra_clauses)
+                    .with_cut_side(cut_side)
+                    .with_paranoid(paranoid),
+            )
+        },
+    )
+}
+
+fn get_uip_solver(opt: &Opt, k: usize) -> Box<dyn ConflictAnalysisFactory> {
+    let weight_heuristic = KthUIPFunction::new(k, opt.weight_decay);
+
+    match opt.uip_weights {
+        Weights::Integer => get_cut_solver::<usize, _>(opt, move |_, _| weight_heuristic),
+        Weights::Real => get_cut_solver::<f64, _>(opt, move |_, _| weight_heuristic),
+        Weights::Rational => get_cut_solver::<BigRational, _>(opt, move |_, _| weight_heuristic),
+    }
+}
+
+fn get_conflict_analysis(opt: &Opt, solver: Solver) -> Box<dyn ConflictAnalysisFactory> {
+    match solver {
+        FirstUIPBasic => get_solver::<FirstUIP>(),
+        FirstUIP => get_uip_solver(opt, 1),
+        SecondUIP => get_uip_solver(opt, 2),
+        ThirdUIP => get_uip_solver(opt, 3),
+        AllUIP => get_cut_solver::<LevelWeight, _>(
+            opt,
+            <AllUIPFunction as WeightHeuristic<LevelWeight>>::from_formula,
+        ),
+        SaturatingAllUIP => get_uip_solver(opt, KthUIPFunction::ALL),
+        KthUIP => get_uip_solver(opt, opt.uip_depth),
+        TrailKthUIP => {
+            let k = opt.uip_depth;
+
+            Box::new(
+                move |n: usize, _formula: &Formula| -> Box<dyn DynConflictAnalysis> {
+                    Box::new(uip::KthUIP::new(n, k))
+                },
+            )
+        }
+        TrailAllUIP => get_solver::<uip::AllUIP>(),
+        RelSat => get_cut_solver::<usize, _>(opt, RelSatFunction::from_formula),
+        MinCut => get_cut_solver::<usize, _>(opt, MinCutFunction::from_formula),
+        Lbd => get_cut_solver::<usize, _>(opt, LBDFunction::from_formula),
+        Activity => get_cut_solver::<usize, _>(
+            opt,
+            <ActivityFunction as WeightHeuristic<usize>>::from_formula,
+        ),
+        RealActivity => get_cut_solver::<f64, _>(
+            opt,
+            <ActivityFunction as WeightHeuristic<f64>>::from_formula,
+        ),
+        Hybrid => {
+            let sample_period = opt.sample_period as usize;
+
+            Box::new(
+                move |n: usize, formula: &Formula| -> Box<dyn DynConflictAnalysis> {
+                    Box::new(
+                        HybridMinCut::from_formula(n, formula).with_sample_period(sample_period),
+                    )
+                },
+            )
+        }
+    }
+}
+
+/// Name of the solver on the command line.
+fn solver_name(solver: Solver) -> String {
+    solver.to_possible_value().unwrap().get_name().to_string()
+}
+
+fn write_graph(graph: &ImplicationGraph, format: GraphFormat, path: Option<&Path>) {
+    let graph = graph.render(format);
+
+    match path {
+        None => eprint!("{graph}"),
+        Some(path) => std::fs::write(path, graph).expect("Failed to write the implication graph"),
+    }
 }
 
 fn main() {
     let mut opt = Opt::parse();
 
-    let (n, formula) = cdcl::read_dimacs(&mut BufReader::new(opt.input));
+    // the solver stops at the next conflict or decision, a second signal exits right away
+    let terminate = Arc::new(AtomicBool::new(false));
 
-    let solver = match opt.solver {
-        FirstUIPBasic => get_solver::<FirstUIP>(),
-        FirstUIP => get_solver::<CutFirstUIP>(),
-        SecondUIP => get_solver::<CutSecondUIP>(),
-        ThirdUIP => get_solver::<CutThirdUIP>(),
-        AllUIP => get_solver::<CutAllUIP>(),
-        SaturatingAllUIP => get_solver::<CutSatAllUIP>(),
-        RelSat => get_solver::<CutRelSat>(),
-        MinCut => get_solver::<CutMinimal>(),
+    for signal in [SIGINT, SIGTERM] {
+        flag::register_conditional_shutdown(signal, 1, terminate.clone())
+            .expect("Failed to register the signal handler");
+        flag::register(signal, terminate.clone()).expect("Failed to register the signal handler");
+    }
+
+    let (n, formula, projection) =
+        cdcl::read_dimacs_with_projection(&mut BufReader::new(&mut opt.input));
+
+    let solver = get_conflict_analysis(&opt, opt.solver);
+
+    let solver = match opt.dump_graph {
+        None => solver,
+        Some(conflict) => {
+            let format = match opt.graph_format {
+                Format::Dot => GraphFormat::Dot,
+                Format::Json => GraphFormat::Json,
+            };
+            let path = opt.graph_output.clone();
+
+            Box::new(
+                move |n: usize, formula: &Formula| -> Box<dyn DynConflictAnalysis> {
+                    let path = path.clone();
+
+                    Box::new(DumpGraph::new(
+                        solver.create(n, formula),
+                        conflict as usize,
+                        move |graph| write_graph(graph, format, path.as_deref()),
+                    ))
+                },
+            )
+        }
+    };
+
+    let solver = match opt.trace.clone() {
+        None => solver,
+        Some(path) => {
+            // every solver, with the options of the active one
+            let name = solver_name(opt.solver);
+            let schemes: Vec<_> = Solver::value_variants()
+                .iter()
+                .map(|&scheme| (solver_name(scheme), get_conflict_analysis(&opt, scheme)))
+                .collect();
+
+            Box::new(
+                move |n: usize, formula: &Formula| -> Box<dyn DynConflictAnalysis> {
+                    let mut trace =
+                        BufWriter::new(File::create(&path).expect("Failed to create the trace"));
+
+                    trace.write_all(ConflictTrace::HEADER.as_bytes()).unwrap();
+
+                    Box::new(TraceConflicts::new(
+                        &name,
+                        solver.create(n, formula),
+                        schemes
+                            .iter()
+                            .map(|(name, scheme)| (name.clone(), scheme.create(n, formula)))
+                            .collect(),
+                        move |conflict| trace.write_all(conflict.to_lines().as_bytes()).unwrap(),
+                    ))
+                },
+            )
+        }
     };
 
     let start = SystemTime::now();
 
-    let ans = solver(n, &mut formula.clone());
+    let options = SolverOptions {
+        chronological_backtracking: opt.chronological_backtracking,
+        simplify: opt.simplify,
+        // no restarts, the static decision order would keep every level
+        ..SolverOptions::default()
+    };
+
+    let mut learned = formula.clone();
+    let mut cdcl = CDCL::new(
+        n,
+        &mut learned,
+        decision_heuristic_factory::<DecideFirstVariable>().create(n, &formula),
+        solver.create(n, &formula),
+    )
+    .with_options(options)
+    .with_terminate(terminate);
+
+    // each branch drops the solver before the summary, which flushes the trace even if the
+    // run is interrupted and exits early
+    let (result, ans, statistics, analysis_statistics, models) = if opt.all_models {
+        let blocking = match opt.blocking {
+            Block::Model => Blocking::Model,
+            Block::Decisions => Blocking::Decisions,
+        };
+        let mut enumerator = ModelEnumerator::new(cdcl).with_blocking(blocking);
+        let variables = match projection {
+            Some(projection) => {
+                enumerator = enumerator.with_projection(projection.clone());
+                projection
+            }
+            None => (0..n).collect(),
+        };
+
+        while let Some(model) = enumerator.next_model() {
+            assert!(cdcl::is_satisfying(&formula, &model));
+
+            let literals: Vec<_> = variables
+                .iter()
+                .map(|&variable| {
+                    let literal = variable as Literal;
+
+                    dimacs_literal(if model[variable] { literal } else { !literal }).to_string()
+                })
+                .collect();
+
+            writeln!(opt.output, "{} 0", literals.join(" ")).unwrap();
+        }
+
+        let result = (
+            enumerator.result().unwrap(),
+            None,
+            *enumerator.cdcl().statistics(),
+            enumerator.cdcl().conflict_analysis().named_statistics(),
+            Some(enumerator.models()),
+        );
+
+        drop(enumerator);
+
+        result
+    } else {
+        let result = cdcl.search();
+        let ans = (result == SolveResult::Satisfiable).then(|| cdcl.get_assignment());
+
+        let analysis_statistics = cdcl.conflict_analysis().named_statistics();
+        let statistics = *cdcl.statistics();
+
+        drop(cdcl);
+
+        (result, ans, statistics, analysis_statistics, None)
+    };
 
     writeln!(
         opt.output,
@@ -72,7 +452,46 @@ fn main() {
     )
     .unwrap();
 
+    if options.simplify {
+        writeln!(
+            opt.output,
+            "Simplifications: {}, unwatched clauses: {}, removed literals: {}",
+            statistics.simplifications, statistics.unwatched_clauses, statistics.removed_literals
+        )
+        .unwrap();
+    }
+
+    if !analysis_statistics.is_empty() {
+        let counters: Vec<_> = analysis_statistics
+            .iter()
+            .map(|(name, value)| format!("{name} {value}"))
+            .collect();
+
+        writeln!(opt.output, "Conflict analysis: {}", counters.join(", ")).unwrap();
+    }
+
+    if let Some(models) = models {
+        writeln!(opt.output, "Models: {models}").unwrap();
+    }
+
+    if result == SolveResult::Interrupted {
+        writeln!(
+            opt.output,
+            "Conflicts: {}, decisions: {}, learned clauses: {}",
+            statistics.conflicts,
+            statistics.decisions,
+            learned.len() - formula.len()
+        )
+        .unwrap();
+        writeln!(opt.output, "s UNKNOWN").unwrap();
+        opt.output.flush().unwrap();
+
+        // the run did not finish
+        process::exit(1);
+    }
+
     match ans {
+        None if models.is_some() => {}
         None => {
             writeln!(opt.output, "UNSAT").unwrap();
         }
@@ -50,25 +50,16 @@ impl VariableState {
     }
 
     fn is_true(&self) -> bool {
-        match self {
-            True(_) => true,
-            _ => false,
-        }
+        matches!(self, True(_))
     }
 
     #[allow(dead_code)]
     fn is_false(&self) -> bool {
-        match self {
-            False(_) => true,
-            _ => false,
-        }
+        matches!(self, False(_))
     }
 
     fn is_unset(&self) -> bool {
-        match self {
-            Unset => true,
-            _ => false,
-        }
+        matches!(self, Unset)
     }
 }
 
@@ -167,6 +158,7 @@ pub fn cdcl_solve(formula: &mut Formula) -> Option<Vec<bool>> {
     }
 }
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug)]
 struct CDCL<'a> {
     trail: Trail,
@@ -218,11 +210,7 @@ impl<'a> CDCL<'a> {
                     Falsified => {
                         ans = Unit(literal);
                     }
-                    Unit(known) => {
-                        if literal != known {
-                            ans = Watched(known, literal)
-                        }
-                    }
+                    Unit(known) if literal != known => ans = Watched(known, literal),
                     _ => {}
                 },
                 False(_) => {
@@ -1,85 +1,257 @@
-pub(crate) mod graph;
+//! Maximum flows and minimum cuts.
+//!
+//! [`FlowGraphBuilder`] and [`FlowGraph`] are the typed interface: vertices and edges are
+//! referred to by handles, and the results are queried as flow values, per-edge flows and
+//! minimum cuts. Vertices may have capacities too, cut like edges.
+//!
+//! ```
+//! use ail_project::sk1flow::FlowGraphBuilder;
+//!
+//! let mut builder = FlowGraphBuilder::new();
+//! let [s, a, b, t] = [(); 4].map(|_| builder.add_vertex());
+//!
+//! builder.add_edge(s, a, 3u64);
+//! builder.add_edge(s, b, 2);
+//! let ab = builder.add_edge(a, b, 1);
+//! let at = builder.add_edge(a, t, 1);
+//! let bt = builder.add_edge(b, t, 3);
+//!
+//! let mut graph = builder.build();
+//!
+//! assert_eq!(graph.max_flow(s, t), 4);
+//! assert_eq!(graph.edge_flow(ab), 1);
+//! assert_eq!(graph.min_cut().edges(), &[at, bt]);
+//! ```
+//!
+//! Below it, the [`MaxFlow`] algorithms work on a residual [`Network`] addressed by raw
+//! indices, where every vertex may hold some excess to push. A [`VertexNetwork`] adds vertex
+//! capacities on top of one, splitting the capacitated vertices internally unless their only edge
+//! in can carry the capacity; the minimum cut conflict analysis keeps such a network alongside
+//! the trail. The [`certificate`] module checks the result of a flow against the network before
+//! it and decomposes the flow into paths.
+
+pub mod certificate;
+pub mod dinic;
+pub mod flow_graph;
+pub mod graph;
+pub mod network;
+pub mod push_relabel;
+pub mod vertex_network;
 
-use crate::sk1flow::graph::Edge;
 use graph::WeightedEdge;
-use std::collections::VecDeque;
 use std::mem;
 use std::ops::{AddAssign, SubAssign};
-#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
-pub struct SK1Flow<T: Default + Clone + Ord>
-where
-    for<'a> T: AddAssign<&'a T> + SubAssign<&'a T>,
+
+use num::rational::Ratio;
+use num::traits::NumAssign;
+use num::{BigInt, BigUint, Integer};
+
+pub use certificate::{FlowDecomposition, FlowError, FlowPath};
+pub use dinic::Dinic;
+pub use flow_graph::{EdgeId, FlowGraph, FlowGraphBuilder, MinCut, VertexId};
+pub use network::Network;
+pub use push_relabel::PushRelabel;
+pub use vertex_network::VertexNetwork;
+
+/// Values usable as capacities, flows and excesses.
+pub trait Capacity:
+    Default + Clone + PartialOrd + for<'a> AddAssign<&'a Self> + for<'a> SubAssign<&'a Self>
 {
-    edges: Vec<(usize, T)>,
-    graph: Vec<Vec<usize>>,
-    excess: Vec<T>,
+    /// Whether the value is too small to push; only zero for exact values.
+    fn is_negligible(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Adds `other`, stopping at the largest value of bounded types instead of overflowing, and
+    /// returns whether it stopped there.
+    fn saturating_add_assign(&mut self, other: &Self) -> bool {
+        *self += other;
+
+        false
+    }
 }
 
-#[allow(dead_code)]
-impl<T: Default + Clone + Ord> SK1Flow<T>
-where
-    for<'a> T: AddAssign<&'a T> + SubAssign<&'a T>,
-{
+macro_rules! exact_capacity {
+    ($($t:ty),*) => {
+        $(impl Capacity for $t {})*
+    };
+}
+
+macro_rules! bounded_capacity {
+    ($($t:ty),*) => {
+        $(impl Capacity for $t {
+            fn saturating_add_assign(&mut self, other: &Self) -> bool {
+                let (sum, overflow) = self.overflowing_add(*other);
+
+                *self = if overflow { <$t>::MAX } else { sum };
+
+                overflow
+            }
+        })*
+    };
+}
+
+bounded_capacity!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+exact_capacity!(BigUint, BigInt);
+
+impl<T: Clone + Integer + NumAssign> Capacity for Ratio<T> {}
+
+/// Floating-point values below this are treated as zero, the rounding errors of capacities up to
+/// about a million stay below it.
+pub const FLOAT_EPSILON: f64 = 1e-7;
+
+impl Capacity for f64 {
+    fn is_negligible(&self) -> bool {
+        self.abs() < FLOAT_EPSILON
+    }
+}
+
+impl Capacity for f32 {
+    fn is_negligible(&self) -> bool {
+        (self.abs() as f64) < FLOAT_EPSILON
+    }
+}
+
+/// The smaller of `a` and `b`, `a` if they are not comparable.
+fn min_capacity<T: PartialOrd>(a: T, b: T) -> T {
+    if b < a {
+        b
+    } else {
+        a
+    }
+}
+
+/// A maximum flow algorithm working on a residual [`Network`]: the excess of the vertices is
+/// pushed towards a sink.
+pub trait MaxFlow<T: Capacity>: Default {
+    fn network(&self) -> &Network<T>;
+    fn network_mut(&mut self) -> &mut Network<T>;
+
+    // the remaining methods forward to the network
+
+    /// Pushes as much excess as possible into `sink` and returns it. Excess that cannot reach the
+    /// sink may be left at inner vertices.
+    fn flow(&mut self, sink: usize) -> T;
+
+    /// Like `flow`, but checks the flow and the minimum cut closest to the sink against the
+    /// network before it, see [`certificate::verify`].
+    fn certified_flow(&mut self, sink: usize) -> Result<T, FlowError> {
+        let before = self.network().clone();
+        let value = self.flow(sink);
+
+        certificate::verify(&before, self.network(), sink, &value, &self.cut(sink))?;
+
+        Ok(value)
+    }
+
+    /// Sink side of the minimum cut closest to the sink; call after `flow`.
+    fn cut(&self, sink: usize) -> Vec<bool> {
+        self.network().reaching(sink)
+    }
+
+    /// Sink side of the minimum cut closest to the source; call after `flow`.
+    fn source_cut(&mut self, source: usize) -> Vec<bool> {
+        // return the excess stuck at inner vertices, so that the residual graph is one of a flow
+        self.flow(source);
+
+        let reachable = self.network().reachable(source);
+
+        reachable.iter().map(|reachable| !reachable).collect()
+    }
+
+    fn add_vertex(&mut self, excess: T) {
+        self.network_mut().add_vertex(excess)
+    }
+
+    fn add_edge(&mut self, from: usize, to: usize, capacity: T) {
+        self.network_mut().add_edge(from, to, capacity)
+    }
+
+    fn truncate(&mut self, vertices: usize, edges: usize) {
+        self.network_mut().truncate(vertices, edges)
+    }
+
+    fn set_capacity(&mut self, id: usize, capacity: T) {
+        self.network_mut().set_capacity(id, capacity)
+    }
+
+    fn set_excess(&mut self, v: usize, excess: T) {
+        self.network_mut().set_excess(v, excess)
+    }
+
+    fn adjacent<'a>(&'a self, v: usize) -> &'a [usize]
+    where
+        T: 'a,
+    {
+        self.network().adjacent(v)
+    }
+
+    fn vertex_count(&self) -> usize {
+        self.network().vertex_count()
+    }
+
+    fn edge_count(&self) -> usize {
+        self.network().edge_count()
+    }
+
+    fn edges(&self) -> &Vec<(usize, T)> {
+        self.network().edges()
+    }
+
+    fn excess(&self) -> &Vec<T> {
+        self.network().excess()
+    }
+}
+
+/// Layered excess pushing: each round pushes along the layers of distances to the sink until
+/// no excess moves anymore.
+#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct SK1Flow<T: Capacity> {
+    network: Network<T>,
+}
+
+impl<T: Capacity> SK1Flow<T> {
     pub fn new() -> Self {
         Self {
-            edges: Vec::new(),
-            graph: Vec::new(),
-            excess: Vec::new(),
+            network: Network::default(),
         }
     }
 
+    /// Network of the adjacency lists `input_graph`, with the excess of every vertex.
     pub fn from_graph(input_graph: &[Vec<impl WeightedEdge<T>>], excess: Vec<T>) -> Self {
         assert_eq!(input_graph.len(), excess.len());
 
-        let mut ans = Self {
-            edges: Vec::new(),
-            graph: vec![vec![]; input_graph.len()],
-            excess,
-        };
+        let mut ans = Self::new();
+
+        for excess in excess {
+            ans.add_vertex(excess);
+        }
 
-        for v in 0..input_graph.len() {
-            for edge in &input_graph[v] {
+        for (v, edges) in input_graph.iter().enumerate() {
+            for edge in edges {
                 ans.add_edge(v, edge.to(), edge.weight().clone());
             }
         }
 
         ans
     }
+}
 
-    pub fn add_vertex(&mut self, excess: T) {
-        self.excess.push(excess);
-        self.graph.push(vec![]);
-    }
-
-    pub fn add_double_edge(&mut self, from: usize, to: usize, capacity: T, reverse_capacity: T) {
-        self.graph[from].push(self.edges.len());
-        self.edges.push((to, capacity));
-        self.graph[to].push(self.edges.len());
-        self.edges.push((from, reverse_capacity));
+impl<T: Capacity> MaxFlow<T> for SK1Flow<T> {
+    fn network(&self) -> &Network<T> {
+        &self.network
     }
 
-    pub fn add_edge(&mut self, from: usize, to: usize, capacity: T) {
-        self.add_double_edge(from, to, capacity, T::default());
+    fn network_mut(&mut self) -> &mut Network<T> {
+        &mut self.network
     }
 
-    fn push_edge(edges: &mut Vec<(usize, T)>, excess: &mut Vec<T>, id: usize) -> T {
-        let v = edges[id ^ 1].0;
-        let u = edges[id].0;
-
-        let value = (&excess[v]).min(&edges[id].1).clone();
-
-        edges[id].1 -= &value;
-        edges[id ^ 1].1 += &value;
-        excess[v] -= &value;
-        excess[u] += &value;
+    fn flow(&mut self, sink: usize) -> T {
+        let network = &mut self.network;
 
-        value
-    }
-
-    pub fn flow(&mut self, sink: usize) -> T {
         loop {
             let mut order = vec![sink];
-            let mut layers = vec![u32::MAX; self.excess.len()];
+            let mut layers = vec![u32::MAX; network.excess.len()];
             layers[sink] = 1;
             let mut id = 0;
 
@@ -87,9 +259,9 @@ where
                 let v = order[id];
                 id += 1;
 
-                for &edge_id in &self.graph[v] {
-                    if self.edges[edge_id ^ 1].1 != Default::default() {
-                        let u = self.edges[edge_id].0;
+                for &edge_id in &network.graph[v] {
+                    if !network.edges[edge_id ^ 1].1.is_negligible() {
+                        let u = network.edges[edge_id].0;
 
                         if layers[u] == u32::MAX {
                             layers[u] = layers[v] + 1;
@@ -102,14 +274,15 @@ where
             let mut change = false;
 
             while let Some(v) = order.pop() {
-                for &edge_id in &self.graph[v] {
-                    if self.excess[v] == Default::default() {
+                for index in 0..network.graph[v].len() {
+                    if network.excess[v].is_negligible() {
                         break;
                     }
 
-                    if layers[self.edges[edge_id].0] == layers[v] - 1 {
-                        change |= Self::push_edge(&mut self.edges, &mut self.excess, edge_id)
-                            != Default::default();
+                    let edge_id = network.graph[v][index];
+
+                    if layers[network.edges[edge_id].0] == layers[v] - 1 {
+                        change |= !network.push_edge(edge_id).is_negligible();
                     }
                 }
             }
@@ -119,33 +292,6 @@ where
             }
         }
 
-        mem::replace(&mut self.excess[sink], T::default())
-    }
-
-    pub fn cut(&self, sink: usize) -> Vec<bool> {
-        let mut used = vec![false; self.graph.len()];
-
-        let mut q = VecDeque::new();
-        q.push_back(sink);
-
-        while let Some(v) = q.pop_front() {
-            if !mem::replace(&mut used[v], true) {
-                for edge in &self.graph[v] {
-                    if self.edges[edge ^ 1].1 != Default::default() {
-                        q.push_back(self.edges[*edge].to());
-                    }
-                }
-            }
-        }
-
-        used
-    }
-
-    pub fn edges(&self) -> &Vec<(usize, T)> {
-        &self.edges
-    }
-
-    pub fn excess(&self) -> &Vec<T> {
-        &self.excess
+        mem::take(&mut network.excess[sink])
     }
 }
@@ -0,0 +1,337 @@
+use std::fmt;
+use std::mem;
+
+use super::{min_capacity, Capacity, Network};
+
+/// Flow along a path or a cycle of edge ids.
+#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct FlowPath<T> {
+    pub edges: Vec<usize>,
+    pub value: T,
+}
+
+/// Flow pushed through a network, split into paths from the vertices that lost excess to the
+/// vertices that gained some, and cycles.
+#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct FlowDecomposition<T> {
+    pub paths: Vec<FlowPath<T>>,
+    pub cycles: Vec<FlowPath<T>>,
+}
+
+/// Why a flow failed verification.
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum FlowError {
+    /// The networks do not have the same vertices and edges.
+    Shape,
+    /// The edge carries more than its capacity, or its capacity changed.
+    Capacity { edge: usize },
+    /// The excess of the vertex does not match the flow through it.
+    Conservation { vertex: usize },
+    /// The flow value is not the excess the sink gained.
+    Value,
+    /// The sink is on the source side of the cut.
+    SinkSide,
+    /// The edge crosses the cut with some capacity left, the flow is not maximum.
+    Unsaturated { edge: usize },
+    /// The capacity of the cut is not the flow value.
+    CutCapacity,
+    /// The paths into the sink do not carry the flow value.
+    Decomposition,
+}
+
+impl fmt::Display for FlowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FlowError::Shape => write!(f, "the network changed shape"),
+            FlowError::Capacity { edge } => write!(f, "capacity of edge {edge} violated"),
+            FlowError::Conservation { vertex } => {
+                write!(f, "flow not conserved at vertex {vertex}")
+            }
+            FlowError::Value => write!(f, "the flow value is not the excess of the sink"),
+            FlowError::SinkSide => write!(f, "the sink is not on the sink side of the cut"),
+            FlowError::Unsaturated { edge } => write!(f, "edge {edge} crosses the cut unsaturated"),
+            FlowError::CutCapacity => write!(f, "the cut capacity differs from the flow value"),
+            FlowError::Decomposition => write!(f, "the flow paths do not carry the flow value"),
+        }
+    }
+}
+
+impl std::error::Error for FlowError {}
+
+fn difference<T: Capacity>(larger: &T, smaller: &T) -> T {
+    let mut difference = larger.clone();
+    difference -= smaller;
+
+    difference
+}
+
+/// Difference of two sums, built from terms that cancel as they come, so that it does not
+/// overflow with infinite capacities.
+struct Balance<T> {
+    plus: T,
+    minus: T,
+}
+
+impl<T: Capacity> Balance<T> {
+    fn new() -> Self {
+        Self {
+            plus: T::default(),
+            minus: T::default(),
+        }
+    }
+
+    fn add(&mut self, term: &T) {
+        if self.minus >= *term {
+            self.minus -= term;
+        } else {
+            self.plus += &difference(term, &self.minus);
+            self.minus = T::default();
+        }
+    }
+
+    fn sub(&mut self, term: &T) {
+        mem::swap(&mut self.plus, &mut self.minus);
+        self.add(term);
+        mem::swap(&mut self.plus, &mut self.minus);
+    }
+
+    /// Adds `a - b`.
+    fn add_difference(&mut self, a: &T, b: &T) {
+        match a < b {
+            true => self.sub(&difference(b, a)),
+            false => self.add(&difference(a, b)),
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.plus.is_negligible() && self.minus.is_negligible()
+    }
+}
+
+/// Flow pushed from `before` to `after`, two states of the same network, where `value` was
+/// taken out of the excess of `sink`. The flow must conserve the excess, see [`verify`].
+pub fn decompose<T: Capacity>(
+    before: &Network<T>,
+    after: &Network<T>,
+    sink: usize,
+    value: &T,
+) -> FlowDecomposition<T> {
+    let n = after.vertex_count();
+
+    // the residual capacity an edge lost is the flow it carries
+    let mut flow: Vec<T> = before
+        .edges
+        .iter()
+        .zip(&after.edges)
+        .map(|((_, before), (_, after))| match after < before {
+            true => difference(before, after),
+            false => T::default(),
+        })
+        .collect();
+
+    let mut lost = vec![T::default(); n];
+    let mut gained = vec![T::default(); n];
+
+    for v in 0..n {
+        let mut excess = after.excess[v].clone();
+
+        if v == sink {
+            excess += value;
+        }
+
+        match excess < before.excess[v] {
+            true => lost[v] = difference(&before.excess[v], &excess),
+            false => gained[v] = difference(&excess, &before.excess[v]),
+        }
+    }
+
+    let mut decomposition = FlowDecomposition {
+        paths: vec![],
+        cycles: vec![],
+    };
+
+    // the edges before `current` carry no flow anymore
+    let mut current = vec![0; n];
+    // number of path edges before each vertex of the walk
+    let mut position = vec![None; n];
+
+    // pushes the most flow the edges carry, at most `limit`, off them
+    let take = |flow: &mut Vec<T>, edges: Vec<usize>, limit: Option<T>| {
+        let value = edges
+            .iter()
+            .map(|&id| flow[id].clone())
+            .chain(limit)
+            .reduce(min_capacity)
+            .unwrap_or_default();
+
+        for &id in &edges {
+            flow[id] -= &value;
+        }
+
+        FlowPath { edges, value }
+    };
+
+    for start in 0..n {
+        while !lost[start].is_negligible() {
+            let mut path = vec![];
+            let mut v = start;
+            let mut stuck = false;
+            position[start] = Some(0);
+
+            while gained[v].is_negligible() {
+                // by conservation, flow entering a vertex that gained nothing leaves it, up to
+                // rounding errors
+                let Some(offset) = after.graph[v][current[v]..]
+                    .iter()
+                    .position(|&id| !flow[id].is_negligible())
+                else {
+                    stuck = true;
+                    break;
+                };
+
+                current[v] += offset;
+
+                let id = after.graph[v][current[v]];
+                let u = after.head(id);
+
+                path.push(id);
+
+                if let Some(p) = position[u] {
+                    let cycle: Vec<_> = path.drain(p..).collect();
+
+                    for &id in &cycle {
+                        position[after.head(id)] = None;
+                    }
+
+                    position[u] = Some(p);
+                    decomposition.cycles.push(take(&mut flow, cycle, None));
+                } else {
+                    position[u] = Some(path.len());
+                }
+
+                v = u;
+            }
+
+            for &id in &path {
+                position[after.head(id)] = None;
+            }
+            position[start] = None;
+
+            if stuck {
+                lost[start] = T::default();
+
+                continue;
+            }
+
+            let limit = min_capacity(lost[start].clone(), gained[v].clone());
+            let path = take(&mut flow, path, Some(limit));
+
+            lost[start] -= &path.value;
+            gained[v] -= &path.value;
+            decomposition.paths.push(path);
+        }
+    }
+
+    decomposition
+}
+
+/// Checks that `after` holds a maximum flow into `sink` pushed from `before`, two states of the
+/// same network, where `value` was taken out of the excess of the sink as the flow algorithms
+/// do: the flow keeps to the capacities of `before` and conserves the excess, and the cut of
+/// `sink_side` is saturated with a capacity of the flow value. Returns the decomposition of the
+/// flow.
+pub fn verify<T: Capacity>(
+    before: &Network<T>,
+    after: &Network<T>,
+    sink: usize,
+    value: &T,
+    sink_side: &[bool],
+) -> Result<FlowDecomposition<T>, FlowError> {
+    let zero = T::default();
+
+    if before.graph != after.graph
+        || before.edges.len() != after.edges.len()
+        || sink_side.len() != after.vertex_count()
+    {
+        return Err(FlowError::Shape);
+    }
+
+    for (id, ((before_head, before_residual), (after_head, after_residual))) in
+        before.edges.iter().zip(&after.edges).enumerate()
+    {
+        if before_head != after_head || *after_residual < zero && !after_residual.is_negligible() {
+            return Err(FlowError::Capacity { edge: id });
+        }
+
+        // the flow moves capacity between an edge and its reverse
+        if id % 2 == 0 {
+            let mut balance = Balance::new();
+            balance.add_difference(before_residual, after_residual);
+            balance.add_difference(before.residual(id ^ 1), after.residual(id ^ 1));
+
+            if !balance.is_zero() {
+                return Err(FlowError::Capacity { edge: id });
+            }
+        }
+    }
+
+    // pushing along an edge takes the same amount from the excess of its tail and from its
+    // residual capacity, and adds it to the excess of its head and to the reverse
+    for v in 0..after.vertex_count() {
+        let mut balance = Balance::new();
+        balance.add_difference(&before.excess[v], &after.excess[v]);
+
+        for &id in after.adjacent(v) {
+            balance.add_difference(after.residual(id), before.residual(id));
+        }
+
+        if v == sink {
+            balance.sub(value);
+
+            if !balance.is_zero() {
+                return Err(FlowError::Value);
+            }
+        } else if !balance.is_zero() {
+            return Err(FlowError::Conservation { vertex: v });
+        }
+    }
+
+    if !sink_side[sink] {
+        return Err(FlowError::SinkSide);
+    }
+
+    let mut cut_balance = Balance::new();
+    cut_balance.add(&before.excess[sink]);
+    cut_balance.sub(value);
+
+    for (id, (head, residual)) in after.edges.iter().enumerate() {
+        if !sink_side[after.tail(id)] && sink_side[*head] {
+            if !residual.is_negligible() {
+                return Err(FlowError::Unsaturated { edge: id });
+            }
+
+            cut_balance.add(before.residual(id));
+        }
+    }
+
+    if !cut_balance.is_zero() {
+        return Err(FlowError::CutCapacity);
+    }
+
+    let decomposition = decompose(before, after, sink, value);
+    let mut sink_balance = Balance::new();
+    sink_balance.add(&before.excess[sink]);
+    sink_balance.sub(value);
+
+    for path in &decomposition.paths {
+        if path.edges.last().map(|&id| after.head(id)) == Some(sink) {
+            sink_balance.add(&path.value);
+        }
+    }
+
+    if !sink_balance.is_zero() {
+        return Err(FlowError::Decomposition);
+    }
+
+    Ok(decomposition)
+}
@@ -0,0 +1,134 @@
+use std::mem;
+
+use super::{min_capacity, Capacity, MaxFlow, Network};
+
+/// Dinic's algorithm: blocking flows along the layers of distances to the sink, every vertex
+/// with excess acting as a source.
+#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct Dinic<T: Capacity> {
+    network: Network<T>,
+}
+
+impl<T: Capacity> Dinic<T> {
+    /// Distances to the sink in the residual network, `usize::MAX` for the unreachable vertices.
+    fn layers(&self, sink: usize) -> Vec<usize> {
+        let network = &self.network;
+
+        let mut layers = vec![usize::MAX; network.vertex_count()];
+        let mut order = vec![sink];
+        layers[sink] = 0;
+        let mut id = 0;
+
+        while id < order.len() {
+            let v = order[id];
+            id += 1;
+
+            for &edge_id in &network.graph[v] {
+                let u = network.edges[edge_id].0;
+
+                if !network.edges[edge_id ^ 1].1.is_negligible() && layers[u] == usize::MAX {
+                    layers[u] = layers[v] + 1;
+                    order.push(u);
+                }
+            }
+        }
+
+        layers
+    }
+
+    /// Sends the excess of `start` along one shortest path to `sink`, returns the amount sent.
+    fn augment(
+        &mut self,
+        start: usize,
+        sink: usize,
+        layers: &mut [usize],
+        current: &mut [usize],
+    ) -> T {
+        let network = &mut self.network;
+        let mut path = vec![];
+        let mut v = start;
+
+        'advance: while v != sink {
+            while current[v] < network.graph[v].len() {
+                let edge_id = network.graph[v][current[v]];
+                let u = network.edges[edge_id].0;
+
+                if !network.edges[edge_id].1.is_negligible()
+                    && layers[u] != usize::MAX
+                    && layers[u] + 1 == layers[v]
+                {
+                    path.push(edge_id);
+                    v = u;
+
+                    continue 'advance;
+                }
+
+                current[v] += 1;
+            }
+
+            // dead end, nothing goes through `v` anymore in this phase
+            layers[v] = usize::MAX;
+
+            match path.pop() {
+                None => return T::default(),
+                Some(edge_id) => {
+                    v = network.edges[edge_id ^ 1].0;
+                    current[v] += 1;
+                }
+            }
+        }
+
+        let mut value = network.excess[start].clone();
+
+        for &edge_id in &path {
+            value = min_capacity(value, network.edges[edge_id].1.clone());
+        }
+
+        for &edge_id in &path {
+            network.edges[edge_id].1 -= &value;
+            network.edges[edge_id ^ 1].1 += &value;
+        }
+
+        network.excess[start] -= &value;
+        network.excess[sink] += &value;
+
+        value
+    }
+}
+
+impl<T: Capacity> MaxFlow<T> for Dinic<T> {
+    fn network(&self) -> &Network<T> {
+        &self.network
+    }
+
+    fn network_mut(&mut self) -> &mut Network<T> {
+        &mut self.network
+    }
+
+    fn flow(&mut self, sink: usize) -> T {
+        loop {
+            let mut layers = self.layers(sink);
+            let mut current = vec![0; self.network.vertex_count()];
+
+            let sources: Vec<_> = (0..self.network.vertex_count())
+                .filter(|&v| {
+                    v != sink && layers[v] != usize::MAX && !self.network.excess[v].is_negligible()
+                })
+                .collect();
+
+            if sources.is_empty() {
+                break;
+            }
+
+            for v in sources {
+                while !self.network.excess[v].is_negligible()
+                    && !self
+                        .augment(v, sink, &mut layers, &mut current)
+                        .is_negligible()
+                {}
+            }
+        }
+
+        mem::take(&mut self.network.excess[sink])
+    }
+}
@@ -0,0 +1,303 @@
+use super::{Capacity, MaxFlow, SK1Flow, VertexNetwork};
+
+/// Handle of a vertex of a [`FlowGraph`].
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct VertexId(usize);
+
+impl VertexId {
+    /// Position of the vertex in creation order.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// Handle of an edge of a [`FlowGraph`].
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct EdgeId(usize);
+
+impl EdgeId {
+    /// Position of the edge in creation order.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// Builds a [`FlowGraph`] from vertices, possibly with capacities, and directed edges with
+/// capacities.
+#[derive(Debug, Default, Clone)]
+pub struct FlowGraphBuilder<T: Capacity> {
+    network: VertexNetwork<T>,
+    vertex_capacities: Vec<Option<T>>,
+    edges: Vec<Edge<T>>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct Edge<T> {
+    id: usize,
+    from: usize,
+    to: usize,
+    capacity: T,
+}
+
+impl<T: Capacity> FlowGraphBuilder<T> {
+    pub fn new() -> Self {
+        Self {
+            network: VertexNetwork::default(),
+            vertex_capacities: vec![],
+            edges: vec![],
+        }
+    }
+
+    /// Adds a vertex letting any flow through.
+    pub fn add_vertex(&mut self) -> VertexId {
+        self.vertex_capacities.push(None);
+
+        VertexId(self.network.add_vertex(None))
+    }
+
+    /// Adds a vertex letting at most `capacity` through.
+    pub fn add_vertex_with_capacity(&mut self, capacity: T) -> VertexId {
+        self.vertex_capacities.push(Some(capacity.clone()));
+
+        VertexId(self.network.add_vertex(Some(capacity)))
+    }
+
+    /// Adds an edge from `from` to `to`; panics if either vertex was not created by this builder.
+    pub fn add_edge(&mut self, from: VertexId, to: VertexId, capacity: T) -> EdgeId {
+        assert!(from.0 < self.network.vertex_count() && to.0 < self.network.vertex_count());
+
+        self.edges.push(Edge {
+            id: self.network.add_edge(from.0, to.0, capacity.clone()),
+            from: from.0,
+            to: to.0,
+            capacity,
+        });
+
+        EdgeId(self.edges.len() - 1)
+    }
+
+    /// The graph, flows computed with the default [`SK1Flow`] algorithm.
+    pub fn build(self) -> FlowGraph<T> {
+        self.build_with()
+    }
+
+    /// The graph, flows computed with the algorithm `F`.
+    pub fn build_with<F: MaxFlow<T>>(self) -> FlowGraph<T, F> {
+        FlowGraph {
+            network: self.network.with_backend(),
+            vertex_capacities: self.vertex_capacities,
+            edges: self.edges,
+            solution: None,
+        }
+    }
+}
+
+/// A directed graph with edge and vertex capacities, answering maximum flow and minimum cut
+/// queries. The capacities of the source and the sink themselves are not used.
+#[derive(Debug, Default, Clone)]
+pub struct FlowGraph<T: Capacity, F: MaxFlow<T> = SK1Flow<T>> {
+    network: VertexNetwork<T, F>,
+    vertex_capacities: Vec<Option<T>>,
+    edges: Vec<Edge<T>>,
+    /// Source, sink and value of the last computed flow.
+    solution: Option<(usize, usize, T)>,
+}
+
+impl<T: Capacity, F: MaxFlow<T>> FlowGraph<T, F> {
+    pub fn vertex_count(&self) -> usize {
+        self.network.vertex_count()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn vertices(&self) -> impl Iterator<Item = VertexId> {
+        (0..self.vertex_count()).map(VertexId)
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = EdgeId> {
+        (0..self.edge_count()).map(EdgeId)
+    }
+
+    /// Tail and head of `edge`.
+    pub fn endpoints(&self, edge: EdgeId) -> (VertexId, VertexId) {
+        let edge = &self.edges[edge.0];
+
+        (VertexId(edge.from), VertexId(edge.to))
+    }
+
+    pub fn capacity(&self, edge: EdgeId) -> &T {
+        &self.edges[edge.0].capacity
+    }
+
+    /// Capacity of `vertex`, `None` if it lets any flow through.
+    pub fn vertex_capacity(&self, vertex: VertexId) -> Option<&T> {
+        self.vertex_capacities[vertex.0].as_ref()
+    }
+
+    /// Computes a maximum flow from `source` to `sink`, replacing any previous one, and returns
+    /// its value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the maximum flow does not fit in a bounded capacity type.
+    pub fn max_flow(&mut self, source: VertexId, sink: VertexId) -> T {
+        assert_ne!(source, sink);
+
+        // the source may send everything its edges can carry
+        let mut supply = T::default();
+        let mut saturated = false;
+
+        for edge in &self.edges {
+            self.network.set_capacity(edge.id, edge.capacity.clone());
+
+            if edge.from == source.0 {
+                saturated |= supply.saturating_add_assign(&edge.capacity);
+            }
+        }
+
+        for (v, capacity) in self.vertex_capacities.iter().enumerate() {
+            if let Some(capacity) = capacity {
+                self.network.set_vertex_capacity(v, capacity.clone());
+            }
+
+            self.network.set_excess(v, T::default());
+        }
+
+        self.network.set_excess(source.0, supply);
+
+        let value = self.network.flow(sink.0);
+
+        // return the excess stuck at inner vertices, so that the edges carry a flow
+        let source_out = self.network.ends(source.0).1;
+        self.network.backend_mut().flow(source_out);
+
+        // a capped supply may all be sent while the residual network still reaches the sink
+        if saturated {
+            let sink_in = self.network.ends(sink.0).0;
+
+            assert!(
+                !self.network.network().reachable(source_out)[sink_in],
+                "The maximum flow exceeds the largest capacity value"
+            );
+        }
+
+        self.solution = Some((source.0, sink.0, value.clone()));
+
+        value
+    }
+
+    /// Value of the last computed flow.
+    pub fn flow_value(&self) -> Option<&T> {
+        self.solution.as_ref().map(|(_, _, value)| value)
+    }
+
+    /// Flow through `edge` in the last computed flow, zero before any.
+    pub fn edge_flow(&self, edge: EdgeId) -> T {
+        let edge = &self.edges[edge.0];
+
+        let mut flow = edge.capacity.clone();
+        flow -= self.network.network().residual(edge.id);
+
+        flow
+    }
+
+    /// Flow through `vertex` in the last computed flow, `None` if it has no capacity.
+    pub fn vertex_flow(&self, vertex: VertexId) -> Option<T> {
+        let capacity = self.vertex_capacities[vertex.0].as_ref()?;
+        let id = self.network.inner_edge(vertex.0)?;
+
+        let mut flow = capacity.clone();
+        flow -= self.network.network().residual(id);
+
+        Some(flow)
+    }
+
+    /// The minimum cut closest to the sink of the last computed flow.
+    pub fn min_cut(&self) -> MinCut {
+        let (_, sink, _) = self.solution.as_ref().expect("no flow was computed");
+        let sink_in = self.network.ends(*sink).0;
+
+        self.make_cut(self.network.backend().cut(sink_in))
+    }
+
+    /// The minimum cut closest to the source of the last computed flow.
+    pub fn source_min_cut(&self) -> MinCut {
+        let (source, _, _) = self.solution.as_ref().expect("no flow was computed");
+        let source_out = self.network.ends(*source).1;
+        let reachable = self.network.network().reachable(source_out);
+
+        self.make_cut(reachable.iter().map(|reachable| !reachable).collect())
+    }
+
+    /// Cut of the sides of the underlying network.
+    fn make_cut(&self, sink_side: Vec<bool>) -> MinCut {
+        let edges = self
+            .edges
+            .iter()
+            .enumerate()
+            .filter(|(_, edge)| {
+                !sink_side[self.network.ends(edge.from).1]
+                    && sink_side[self.network.ends(edge.to).0]
+            })
+            .map(|(index, _)| EdgeId(index))
+            .collect();
+
+        let vertices = self
+            .vertices()
+            .filter(|vertex| {
+                let (in_id, out_id) = self.network.ends(vertex.0);
+
+                !sink_side[in_id] && sink_side[out_id]
+            })
+            .collect();
+
+        // a vertex is on the side of its flow in, but the source on the side of its flow out
+        let (source, _, _) = self.solution.as_ref().expect("no flow was computed");
+        let sink_side = self
+            .vertices()
+            .map(|vertex| {
+                let (in_id, out_id) = self.network.ends(vertex.0);
+
+                sink_side[if vertex.0 == *source { out_id } else { in_id }]
+            })
+            .collect();
+
+        MinCut {
+            sink_side,
+            edges,
+            vertices,
+        }
+    }
+}
+
+/// A minimum cut: the vertices on the sink side, and the edges crossing to it and the vertices
+/// whose capacity is cut, which lie on the source side.
+#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct MinCut {
+    sink_side: Vec<bool>,
+    edges: Vec<EdgeId>,
+    vertices: Vec<VertexId>,
+}
+
+impl MinCut {
+    pub fn is_sink_side(&self, vertex: VertexId) -> bool {
+        self.sink_side[vertex.0]
+    }
+
+    pub fn is_source_side(&self, vertex: VertexId) -> bool {
+        !self.sink_side[vertex.0]
+    }
+
+    /// Edges from the source side to the sink side.
+    pub fn edges(&self) -> &[EdgeId] {
+        &self.edges
+    }
+
+    /// Vertices whose capacity is in the cut; with the edges, their capacities sum up to the flow
+    /// value.
+    pub fn vertices(&self) -> &[VertexId] {
+        &self.vertices
+    }
+}
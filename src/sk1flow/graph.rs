@@ -1,7 +1,9 @@
+/// Edge of an adjacency list, as its head.
 pub trait Edge {
     fn to(&self) -> usize;
 }
 
+/// Edge of an adjacency list with a weight.
 pub trait WeightedEdge<T>: Edge {
     fn weight(&self) -> &T;
     fn weight_mut(&mut self) -> &mut T;
@@ -0,0 +1,161 @@
+use std::collections::VecDeque;
+use std::mem;
+
+use super::{min_capacity, Capacity};
+
+/// Residual network shared by the flow algorithms. Edges are stored in pairs, the edge `id ^ 1`
+/// is the reverse of `id`, and every vertex may hold some excess waiting to be pushed.
+#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct Network<T: Capacity> {
+    pub(super) edges: Vec<(usize, T)>,
+    pub(super) graph: Vec<Vec<usize>>,
+    pub(super) excess: Vec<T>,
+}
+
+impl<T: Capacity> Network<T> {
+    pub fn add_vertex(&mut self, excess: T) {
+        self.excess.push(excess);
+        self.graph.push(vec![]);
+    }
+
+    /// Adds the edge `from -> to` and its reverse with their capacities, the edge gets the next
+    /// even id and its reverse the odd one after it.
+    pub fn add_double_edge(&mut self, from: usize, to: usize, capacity: T, reverse_capacity: T) {
+        self.graph[from].push(self.edges.len());
+        self.edges.push((to, capacity));
+        self.graph[to].push(self.edges.len());
+        self.edges.push((from, reverse_capacity));
+    }
+
+    /// Adds the edge `from -> to` with a reverse of zero capacity.
+    pub fn add_edge(&mut self, from: usize, to: usize, capacity: T) {
+        self.add_double_edge(from, to, capacity, T::default());
+    }
+
+    /// Removes the vertices from `vertices` on and the edges from `edges` on, which must only
+    /// touch the remaining vertices through edges from `edges` on.
+    pub fn truncate(&mut self, vertices: usize, edges: usize) {
+        for id in (edges..self.edges.len()).rev() {
+            let from = self.edges[id ^ 1].0;
+            let popped = self.graph[from].pop();
+
+            debug_assert_eq!(popped, Some(id));
+        }
+
+        self.edges.truncate(edges);
+        self.graph.truncate(vertices);
+        self.excess.truncate(vertices);
+    }
+
+    /// Resets the edge `id` and its reverse to `capacity` and zero, dropping any flow on them.
+    pub fn set_capacity(&mut self, id: usize, capacity: T) {
+        self.edges[id].1 = capacity;
+        self.edges[id ^ 1].1 = T::default();
+    }
+
+    pub fn set_excess(&mut self, v: usize, excess: T) {
+        self.excess[v] = excess;
+    }
+
+    /// Ids of the edges leaving `v`, reverse edges included.
+    pub fn adjacent(&self, v: usize) -> &[usize] {
+        &self.graph[v]
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.graph.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Vertex the edge `id` leaves.
+    pub fn tail(&self, id: usize) -> usize {
+        self.edges[id ^ 1].0
+    }
+
+    /// Vertex the edge `id` enters.
+    pub fn head(&self, id: usize) -> usize {
+        self.edges[id].0
+    }
+
+    /// Whether `id` is the reverse of an edge that was added.
+    pub fn is_reverse(&self, id: usize) -> bool {
+        id & 1 == 1
+    }
+
+    /// Id of the reverse of the edge `id`.
+    pub fn reverse(&self, id: usize) -> usize {
+        id ^ 1
+    }
+
+    /// Capacity of the edge `id` left in the residual network.
+    pub fn residual(&self, id: usize) -> &T {
+        &self.edges[id].1
+    }
+
+    /// Edges as pairs of head and residual capacity, indexed by edge id.
+    pub fn edges(&self) -> &Vec<(usize, T)> {
+        &self.edges
+    }
+
+    pub fn excess(&self) -> &Vec<T> {
+        &self.excess
+    }
+
+    /// Pushes as much of the excess of the tail of `id` as the edge allows, returns the amount.
+    pub(super) fn push_edge(&mut self, id: usize) -> T {
+        let v = self.edges[id ^ 1].0;
+        let u = self.edges[id].0;
+
+        let value = min_capacity(&self.excess[v], &self.edges[id].1).clone();
+
+        self.edges[id].1 -= &value;
+        self.edges[id ^ 1].1 += &value;
+        self.excess[v] -= &value;
+        self.excess[u] += &value;
+
+        value
+    }
+
+    /// Vertices that can reach `sink` in the residual network.
+    pub(super) fn reaching(&self, sink: usize) -> Vec<bool> {
+        let mut used = vec![false; self.graph.len()];
+
+        let mut q = VecDeque::new();
+        q.push_back(sink);
+
+        while let Some(v) = q.pop_front() {
+            if !mem::replace(&mut used[v], true) {
+                for edge in &self.graph[v] {
+                    if !self.edges[edge ^ 1].1.is_negligible() {
+                        q.push_back(self.edges[*edge].0);
+                    }
+                }
+            }
+        }
+
+        used
+    }
+
+    /// Vertices reachable from `source` in the residual network.
+    pub(super) fn reachable(&self, source: usize) -> Vec<bool> {
+        let mut used = vec![false; self.graph.len()];
+
+        let mut q = VecDeque::new();
+        q.push_back(source);
+
+        while let Some(v) = q.pop_front() {
+            if !mem::replace(&mut used[v], true) {
+                for edge in &self.graph[v] {
+                    if !self.edges[*edge].1.is_negligible() {
+                        q.push_back(self.edges[*edge].0);
+                    }
+                }
+            }
+        }
+
+        used
+    }
+}
@@ -0,0 +1,203 @@
+use std::mem;
+
+use super::{Capacity, MaxFlow, Network};
+
+/// Highest-label push-relabel with the gap and global relabeling heuristics. Only the first
+/// phase runs: excess that cannot reach the sink stays where it is.
+#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct PushRelabel<T: Capacity> {
+    network: Network<T>,
+}
+
+impl<T: Capacity> MaxFlow<T> for PushRelabel<T> {
+    fn network(&self) -> &Network<T> {
+        &self.network
+    }
+
+    fn network_mut(&mut self) -> &mut Network<T> {
+        &mut self.network
+    }
+
+    fn flow(&mut self, sink: usize) -> T {
+        Discharge::new(&mut self.network, sink).run();
+
+        mem::take(&mut self.network.excess[sink])
+    }
+}
+
+struct Discharge<'a, T: Capacity> {
+    network: &'a mut Network<T>,
+    sink: usize,
+    /// Labels, `n` for the vertices that cannot reach the sink.
+    height: Vec<usize>,
+    current: Vec<usize>,
+    /// Vertices with excess by label, possibly with outdated entries.
+    active: Vec<Vec<usize>>,
+    /// Number of vertices per label below `n`.
+    count: Vec<usize>,
+    highest: usize,
+    /// Work since the last global relabeling.
+    work: usize,
+}
+
+impl<'a, T: Capacity> Discharge<'a, T> {
+    /// Relabels the graph from scratch every time this much work per vertex and edge was done.
+    const GLOBAL_RELABEL_WORK: usize = 6;
+
+    fn new(network: &'a mut Network<T>, sink: usize) -> Self {
+        let n = network.vertex_count();
+
+        Self {
+            network,
+            sink,
+            height: vec![n; n],
+            current: vec![0; n],
+            active: vec![vec![]; n],
+            count: vec![0; n],
+            highest: 0,
+            work: 0,
+        }
+    }
+
+    fn activate(&mut self, v: usize) {
+        let height = self.height[v];
+
+        self.active[height].push(v);
+        self.highest = self.highest.max(height);
+    }
+
+    /// Sets every label to the distance to the sink in the residual network.
+    fn global_relabel(&mut self) {
+        let n = self.height.len();
+
+        self.height.fill(n);
+        self.current.fill(0);
+        self.count.fill(0);
+        self.active.iter_mut().for_each(Vec::clear);
+        self.highest = 0;
+        self.work = 0;
+
+        let mut order = vec![self.sink];
+        self.height[self.sink] = 0;
+        let mut id = 0;
+
+        while id < order.len() {
+            let v = order[id];
+            id += 1;
+
+            self.count[self.height[v]] += 1;
+
+            for &edge_id in &self.network.graph[v] {
+                let u = self.network.edges[edge_id].0;
+
+                if !self.network.edges[edge_id ^ 1].1.is_negligible() && self.height[u] == n {
+                    self.height[u] = self.height[v] + 1;
+                    order.push(u);
+                }
+            }
+        }
+
+        for v in order {
+            if v != self.sink && !self.network.excess[v].is_negligible() {
+                self.activate(v);
+            }
+        }
+    }
+
+    fn relabel(&mut self, v: usize) {
+        let n = self.height.len();
+        let old = self.height[v];
+
+        self.count[old] -= 1;
+        self.current[v] = 0;
+        self.work += self.network.graph[v].len() + Self::GLOBAL_RELABEL_WORK;
+
+        if self.count[old] == 0 {
+            // gap: nothing above `old` can reach the sink anymore
+            for height in self.height.iter_mut() {
+                if *height > old && *height < n {
+                    self.count[*height] -= 1;
+                    *height = n;
+                }
+            }
+
+            self.height[v] = n;
+
+            return;
+        }
+
+        let mut height = n;
+
+        for &edge_id in &self.network.graph[v] {
+            if !self.network.edges[edge_id].1.is_negligible() {
+                height = height.min(self.height[self.network.edges[edge_id].0] + 1);
+            }
+        }
+
+        self.height[v] = height;
+
+        if height < n {
+            self.count[height] += 1;
+        }
+    }
+
+    fn discharge(&mut self, v: usize) {
+        let n = self.height.len();
+
+        while !self.network.excess[v].is_negligible() {
+            if self.current[v] == self.network.graph[v].len() {
+                self.relabel(v);
+
+                if self.height[v] == n {
+                    break;
+                }
+
+                continue;
+            }
+
+            let edge_id = self.network.graph[v][self.current[v]];
+            let u = self.network.edges[edge_id].0;
+
+            if !self.network.edges[edge_id].1.is_negligible()
+                && self.height[v] == self.height[u] + 1
+            {
+                let idle = self.network.excess[u].is_negligible();
+
+                self.network.push_edge(edge_id);
+
+                if idle && u != self.sink {
+                    self.activate(u);
+                }
+            } else {
+                self.current[v] += 1;
+            }
+        }
+    }
+
+    fn run(&mut self) {
+        let n = self.height.len();
+        let global_relabel_work = Self::GLOBAL_RELABEL_WORK * n + self.network.edge_count();
+
+        self.global_relabel();
+
+        loop {
+            while self.highest > 0 && self.active[self.highest].is_empty() {
+                self.highest -= 1;
+            }
+
+            let Some(v) = self.active[self.highest].pop() else {
+                break;
+            };
+
+            if self.height[v] != self.highest || self.network.excess[v].is_negligible() {
+                continue;
+            }
+
+            self.discharge(v);
+
+            if self.work > global_relabel_work {
+                self.global_relabel();
+            }
+        }
+    }
+}
@@ -0,0 +1,332 @@
+use std::marker::PhantomData;
+use std::mem;
+
+use super::{Capacity, FlowError, MaxFlow, Network, SK1Flow};
+
+/// A flow network whose vertices may have capacities. A vertex with a capacity is split into an
+/// in-vertex and an out-vertex of the underlying [`Network`], joined by an edge, unless its only
+/// edge in carries the capacity; a vertex without one stays a single vertex. Edges are referred
+/// to by their id in the [`Network`].
+///
+/// The excess of a vertex starts after its capacity and the flow into a sink stops before it,
+/// so the capacities of the source and the sink do not matter.
+#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct VertexNetwork<T: Capacity, F: MaxFlow<T> = SK1Flow<T>> {
+    flow: F,
+    /// In-vertex and out-vertex of every vertex, the same one for the vertices without capacity.
+    ends: Vec<(usize, usize)>,
+    /// Edge carrying the capacity of every vertex with one: from its in-vertex to its out-vertex,
+    /// or its only edge in.
+    inner_edges: Vec<Option<usize>>,
+    /// Id of the edge into the gate of every bypass, the edge out of it follows, along with the
+    /// vertex it goes around, in increasing order.
+    bypasses: Vec<(usize, usize)>,
+    /// Vertex of every vertex of the underlying network.
+    owners: Vec<usize>,
+    capacity: PhantomData<T>,
+}
+
+impl<T: Capacity, F: MaxFlow<T>> VertexNetwork<T, F> {
+    /// Adds a vertex, with a capacity or without any, and returns its index.
+    pub fn add_vertex(&mut self, capacity: Option<T>) -> usize {
+        let v = self.ends.len();
+        let in_id = self.flow.vertex_count();
+
+        self.flow.add_vertex(T::default());
+        self.owners.push(v);
+
+        match capacity {
+            None => {
+                self.ends.push((in_id, in_id));
+                self.inner_edges.push(None);
+            }
+            Some(capacity) => {
+                self.flow.add_vertex(T::default());
+                self.owners.push(v);
+                self.ends.push((in_id, in_id + 1));
+                self.inner_edges.push(Some(self.flow.edge_count()));
+                self.flow.add_edge(in_id, in_id + 1, capacity);
+            }
+        }
+
+        v
+    }
+
+    /// Adds a vertex whose only edge in leaves `from` and carries its capacity, so that it stays a
+    /// single vertex, and returns its index. The edge has the id [`edge_count`](Self::edge_count)
+    /// had before.
+    pub fn add_vertex_from(&mut self, from: usize, capacity: T) -> usize {
+        let v = self.ends.len();
+        let id = self.flow.vertex_count();
+
+        self.flow.add_vertex(T::default());
+        self.owners.push(v);
+        self.ends.push((id, id));
+        self.inner_edges.push(Some(self.flow.edge_count()));
+        self.flow.add_edge(self.ends[from].1, id, capacity);
+
+        v
+    }
+
+    /// Adds the edge `from -> to` and returns its id.
+    pub fn add_edge(&mut self, from: usize, to: usize, capacity: T) -> usize {
+        assert!(
+            !self.is_single(to),
+            "a vertex added with its edge in has no other one"
+        );
+
+        let id = self.flow.edge_count();
+
+        self.flow
+            .add_edge(self.ends[from].1, self.ends[to].0, capacity);
+
+        id
+    }
+
+    /// Lets the flow through `v` go around its capacity through `gate` instead, with edges of
+    /// `capacity` into and out of the gate; returns the id of the edge into the gate.
+    pub fn add_bypass(&mut self, v: usize, gate: usize, capacity: T) -> usize {
+        assert!(
+            self.has_capacity(v),
+            "only a vertex with a capacity has a bypass"
+        );
+
+        let id = self.flow.edge_count();
+
+        self.flow
+            .add_edge(self.before_capacity(v), self.ends[gate].0, capacity.clone());
+        self.flow
+            .add_edge(self.ends[gate].1, self.ends[v].1, capacity);
+        self.bypasses.push((id, v));
+
+        id
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.ends.len()
+    }
+
+    /// Number of edge ids in use, the edges inside the vertices included.
+    pub fn edge_count(&self) -> usize {
+        self.flow.edge_count()
+    }
+
+    /// Removes the vertices from `vertices` on and the edges from `edges` on, where `edges` is an
+    /// edge count taken when there were `vertices` vertices or more.
+    pub fn truncate(&mut self, vertices: usize, edges: usize) {
+        let internal = self
+            .ends
+            .get(vertices)
+            .map_or(self.owners.len(), |ends| ends.0);
+
+        self.flow.truncate(internal, edges);
+        self.ends.truncate(vertices);
+        self.inner_edges.truncate(vertices);
+        self.owners.truncate(internal);
+
+        while self.bypasses.last().is_some_and(|&(id, _)| id >= edges) {
+            self.bypasses.pop();
+        }
+    }
+
+    pub fn has_capacity(&self, v: usize) -> bool {
+        self.inner_edges[v].is_some()
+    }
+
+    /// Edge carrying the capacity of `v`, `None` if it has no capacity.
+    pub fn inner_edge(&self, v: usize) -> Option<usize> {
+        self.inner_edges[v]
+    }
+
+    /// Whether `v` has a capacity without being split, see
+    /// [`add_vertex_from`](Self::add_vertex_from).
+    pub fn is_single(&self, v: usize) -> bool {
+        let (in_id, out_id) = self.ends[v];
+
+        in_id == out_id && self.has_capacity(v)
+    }
+
+    /// Vertex of the underlying network the flow through `v` leaves before paying its capacity.
+    fn before_capacity(&self, v: usize) -> usize {
+        match self.inner_edges[v] {
+            Some(id) if self.is_single(v) => self.network().tail(id),
+            _ => self.ends[v].0,
+        }
+    }
+
+    fn is_bypass(&self, id: usize) -> bool {
+        let index = self
+            .bypasses
+            .partition_point(|&(bypass, _)| bypass + 2 < id);
+
+        self.bypasses
+            .get(index)
+            .is_some_and(|&(bypass, _)| bypass == id || bypass + 2 == id)
+    }
+
+    /// Flow on the edge `id`, which must have been reset since it was added.
+    fn edge_flow(&self, id: usize) -> &T {
+        self.network().residual(id ^ 1)
+    }
+
+    /// Resets the capacity of `v`, dropping any flow through it.
+    pub fn set_vertex_capacity(&mut self, v: usize, capacity: T) {
+        let id = self.inner_edges[v].expect("only a vertex with a capacity has one");
+
+        self.flow.set_capacity(id, capacity);
+    }
+
+    /// Resets the capacity of the edge `id`, dropping any flow on it.
+    pub fn set_capacity(&mut self, id: usize, capacity: T) {
+        self.flow.set_capacity(id, capacity);
+    }
+
+    /// Sets the excess of `v`, clearing any excess stuck inside it.
+    pub fn set_excess(&mut self, v: usize, excess: T) {
+        let (in_id, out_id) = self.ends[v];
+
+        self.flow.set_excess(in_id, T::default());
+        self.flow.set_excess(out_id, excess);
+    }
+
+    /// Edges into `v` along with the vertices they leave, bypasses excluded.
+    pub fn in_edges(&self, v: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let network = self.network();
+
+        network
+            .adjacent(self.ends[v].0)
+            .iter()
+            .filter(move |&&id| network.is_reverse(id))
+            .map(move |&id| (network.reverse(id), network.head(id)))
+            .filter(move |&(id, tail)| {
+                self.ends[self.owners[tail]].1 == tail && !self.is_bypass(id)
+            })
+            .map(move |(id, tail)| (id, self.owners[tail]))
+    }
+
+    /// Edges out of `v` along with the vertices they enter, bypasses excluded.
+    pub fn out_edges(&self, v: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let network = self.network();
+
+        network
+            .adjacent(self.ends[v].1)
+            .iter()
+            .filter(move |&&id| !network.is_reverse(id))
+            .map(move |&id| (id, network.head(id)))
+            .filter(move |&(id, head)| {
+                self.ends[self.owners[head]].0 == head && !self.is_bypass(id)
+            })
+            .map(move |(id, head)| (id, self.owners[head]))
+    }
+
+    /// Pushes as much excess as possible into `sink` and returns it.
+    pub fn flow(&mut self, sink: usize) -> T {
+        self.flow.flow(self.ends[sink].0)
+    }
+
+    /// Like `flow`, but checks the flow, see [`MaxFlow::certified_flow`].
+    pub fn certified_flow(&mut self, sink: usize) -> Result<T, FlowError> {
+        self.flow.certified_flow(self.ends[sink].0)
+    }
+
+    /// Vertices whose capacity is in the minimum cut closest to the sink; call after `flow`.
+    pub fn vertex_cut(&self, sink: usize) -> Vec<bool> {
+        let reaching = self.flow.cut(self.ends[sink].0);
+
+        // the in-vertex of a single vertex reaches the sink through its capacity, its bypasses,
+        // or back along its edge in if flow enters it
+        let mut sink_side: Vec<_> = (0..self.vertex_count())
+            .map(|v| match self.inner_edges[v] {
+                Some(id) if self.is_single(v) => {
+                    (!self.network().residual(id).is_negligible() && reaching[self.ends[v].1])
+                        || (!self.edge_flow(id).is_negligible()
+                            && reaching[self.before_capacity(v)])
+                }
+                _ => false,
+            })
+            .collect();
+
+        for &(id, v) in &self.bypasses {
+            sink_side[v] |= (!self.network().residual(id).is_negligible()
+                && reaching[self.network().head(id)])
+                || (!self.edge_flow(id).is_negligible() && reaching[self.before_capacity(v)]);
+        }
+
+        self.cut_vertices(&reaching, &sink_side)
+    }
+
+    /// Vertices whose capacity is in the minimum cut closest to the source; call after `flow`.
+    pub fn source_vertex_cut(&mut self, source: usize) -> Vec<bool> {
+        let unreachable = self.flow.source_cut(self.ends[source].1);
+
+        // the in-vertex of a single vertex is reached along its edge in, or back from its
+        // capacity or its bypasses if flow goes through them
+        let mut reached: Vec<_> = (0..self.vertex_count())
+            .map(|v| match self.inner_edges[v] {
+                Some(id) if self.is_single(v) => {
+                    !unreachable[self.before_capacity(v)]
+                        || (!self.edge_flow(id).is_negligible() && !unreachable[self.ends[v].1])
+                }
+                _ => false,
+            })
+            .collect();
+
+        for &(id, v) in &self.bypasses {
+            reached[v] |=
+                !self.edge_flow(id).is_negligible() && !unreachable[self.network().head(id)];
+        }
+
+        let sink_side: Vec<_> = reached.iter().map(|reached| !reached).collect();
+
+        self.cut_vertices(&unreachable, &sink_side)
+    }
+
+    /// Cut vertices given the sides of the vertices of the underlying network and of the
+    /// in-vertices the single vertices would have if they were split.
+    fn cut_vertices(&self, sink_side: &[bool], single_in_sink_side: &[bool]) -> Vec<bool> {
+        (0..self.vertex_count())
+            .map(|v| {
+                let (in_id, out_id) = self.ends[v];
+                let in_sink_side = if self.is_single(v) {
+                    single_in_sink_side[v]
+                } else {
+                    sink_side[in_id]
+                };
+
+                !in_sink_side && sink_side[out_id]
+            })
+            .collect()
+    }
+
+    /// The underlying network.
+    pub fn network(&self) -> &Network<T> {
+        self.flow.network()
+    }
+
+    /// The same network, flows computed with the algorithm `G`.
+    pub fn with_backend<G: MaxFlow<T>>(mut self) -> VertexNetwork<T, G> {
+        let mut flow = G::default();
+        *flow.network_mut() = mem::take(self.flow.network_mut());
+
+        VertexNetwork {
+            flow,
+            ends: self.ends,
+            inner_edges: self.inner_edges,
+            bypasses: self.bypasses,
+            owners: self.owners,
+            capacity: PhantomData,
+        }
+    }
+
+    pub(super) fn backend(&self) -> &F {
+        &self.flow
+    }
+
+    pub(super) fn backend_mut(&mut self) -> &mut F {
+        &mut self.flow
+    }
+
+    pub(super) fn ends(&self, v: usize) -> (usize, usize) {
+        self.ends[v]
+    }
+}
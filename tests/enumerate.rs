@@ -0,0 +1,234 @@
+use std::collections::BTreeSet;
+use std::io::Cursor;
+
+use ail_project::cdcl::decision::DecideFirstVariable;
+use ail_project::cdcl::enumerate::{Blocking, ModelEnumerator};
+use ail_project::cdcl::first_uip::FirstUIP;
+use ail_project::cdcl::mincut::{CutAllUIP, CutMinimal};
+use ail_project::cdcl::propagation::{ConflictAnalysis, DecisionHeuristic, Trail};
+use ail_project::cdcl::*;
+use rand::{thread_rng, Rng};
+
+/// The distinct projections of the models of `formula`, by trying every assignment.
+fn brute_force(n: usize, formula: &Formula, projection: &[Variable]) -> BTreeSet<Vec<bool>> {
+    (0..1 << n)
+        .map(|bits: usize| (0..n).map(|i| bits >> i & 1 == 1).collect::<Vec<_>>())
+        .filter(|assignment| is_satisfying(formula, assignment))
+        .map(|assignment| projection.iter().map(|&v| assignment[v]).collect())
+        .collect()
+}
+
+/// Decides the projected variables first, so that decisions can block projected models.
+struct DecideProjectedFirst {
+    order: Vec<Variable>,
+}
+
+impl DecideProjectedFirst {
+    fn new(n: usize, projection: &[Variable]) -> Self {
+        let rest = (0..n).filter(|variable| !projection.contains(variable));
+
+        Self {
+            order: projection.iter().copied().chain(rest).collect(),
+        }
+    }
+}
+
+impl DecisionHeuristic for DecideProjectedFirst {
+    fn from_formula(n: usize, _formula: &Formula) -> Self {
+        Self::new(n, &[])
+    }
+
+    fn backtrack_and_add_clause(
+        &mut self,
+        _formula: &Formula,
+        _trail: &Trail,
+        _level: usize,
+        _clause_id: usize,
+    ) {
+    }
+
+    fn propagate_literal(
+        &mut self,
+        _formula: &Formula,
+        _trail: &Trail,
+        _literal: Literal,
+        _reason_id: usize,
+    ) {
+    }
+
+    fn decide_literal(&mut self, _formula: &Formula, trail: &Trail) -> Option<Literal> {
+        self.order
+            .iter()
+            .find(|&&variable| trail.assignment[variable].is_unset())
+            .map(|&variable| variable as Literal)
+    }
+}
+
+/// The projections of the enumerated models, checking that none is repeated.
+fn enumerate<C: ConflictAnalysis>(
+    n: usize,
+    formula: &Formula,
+    projection: Option<&[Variable]>,
+    blocking: Blocking,
+    options: SolverOptions,
+) -> BTreeSet<Vec<bool>> {
+    let d = DecideFirstVariable::from_formula(n, formula);
+
+    enumerate_with::<_, C>(d, n, formula, projection, blocking, options)
+}
+
+fn enumerate_with<D: DecisionHeuristic, C: ConflictAnalysis>(
+    d: D,
+    n: usize,
+    formula: &Formula,
+    projection: Option<&[Variable]>,
+    blocking: Blocking,
+    options: SolverOptions,
+) -> BTreeSet<Vec<bool>> {
+    let mut learned = formula.clone();
+    let c = C::from_formula(n, formula);
+    let cdcl = CDCL::new(n, &mut learned, d, c).with_options(options);
+
+    let mut enumerator = ModelEnumerator::new(cdcl).with_blocking(blocking);
+
+    if let Some(projection) = projection {
+        enumerator = enumerator.with_projection(projection.to_vec());
+    }
+
+    let all: Vec<_> = (0..n).collect();
+    let variables = projection.unwrap_or(&all);
+    let mut models = BTreeSet::new();
+
+    while let Some(model) = enumerator.next_model() {
+        assert!(is_satisfying(formula, &model));
+        assert!(models.insert(variables.iter().map(|&v| model[v]).collect()));
+    }
+
+    assert_eq!(enumerator.result(), Some(SolveResult::Unsatisfiable));
+    assert_eq!(enumerator.models(), models.len());
+
+    models
+}
+
+fn test_enumeration<C: ConflictAnalysis>(options: SolverOptions) {
+    let mut rng = thread_rng();
+    let n = 10;
+
+    for _ in 0..200 {
+        let bnd = n as Literal;
+        let clauses = rng.gen_range(5..45);
+        let formula: Formula = (0..clauses)
+            .map(|_| (0..3).map(|_| rng.gen_range(-bnd..bnd)).collect())
+            .collect();
+
+        let projection: Vec<_> = (0..n).filter(|_| rng.gen_bool(0.4)).collect();
+        let all: Vec<_> = (0..n).collect();
+
+        for blocking in [Blocking::Model, Blocking::Decisions] {
+            assert_eq!(
+                enumerate::<C>(n, &formula, None, blocking, options),
+                brute_force(n, &formula, &all)
+            );
+            assert_eq!(
+                enumerate::<C>(n, &formula, Some(&projection), blocking, options),
+                brute_force(n, &formula, &projection)
+            );
+        }
+    }
+}
+
+#[test]
+fn enumeration_matches_brute_force() {
+    test_enumeration::<FirstUIP>(SolverOptions::default());
+    test_enumeration::<CutMinimal>(SolverOptions::default());
+    test_enumeration::<CutAllUIP>(SolverOptions::default());
+}
+
+#[test]
+fn enumeration_with_solver_options() {
+    test_enumeration::<FirstUIP>(SolverOptions {
+        chronological_backtracking: Some(0),
+        ..SolverOptions::default()
+    });
+    test_enumeration::<CutMinimal>(SolverOptions {
+        chronological_backtracking: Some(1),
+        restart_interval: Some(1),
+        simplify: true,
+    });
+    test_enumeration::<FirstUIP>(SolverOptions {
+        restart_interval: Some(2),
+        simplify: true,
+        ..SolverOptions::default()
+    });
+}
+
+#[test]
+fn projected_decisions_block_projections() {
+    let mut rng = thread_rng();
+    let n = 10;
+
+    for _ in 0..200 {
+        let bnd = n as Literal;
+        let clauses = rng.gen_range(5..45);
+        let formula: Formula = (0..clauses)
+            .map(|_| (0..3).map(|_| rng.gen_range(-bnd..bnd)).collect())
+            .collect();
+
+        let projection: Vec<_> = (0..n).filter(|_| rng.gen_bool(0.4)).collect();
+
+        for options in [
+            SolverOptions::default(),
+            SolverOptions {
+                chronological_backtracking: Some(0),
+                restart_interval: Some(1),
+                simplify: true,
+            },
+        ] {
+            assert_eq!(
+                enumerate_with::<_, CutMinimal>(
+                    DecideProjectedFirst::new(n, &projection),
+                    n,
+                    &formula,
+                    Some(&projection),
+                    Blocking::Decisions,
+                    options,
+                ),
+                brute_force(n, &formula, &projection)
+            );
+        }
+    }
+}
+
+#[test]
+fn projection_from_c_ind_lines() {
+    let dimacs = "c ind 1 3 0\nc a comment\np cnf 4 2\n1 2 0\nc ind 4 0\n-3 -4 0\n";
+    let (n, formula, projection) = read_dimacs_with_projection(&mut Cursor::new(dimacs));
+
+    assert_eq!(n, 4);
+    assert_eq!(formula, [vec![0, 1], vec![-3, -4]]);
+    assert_eq!(projection, Some(vec![0, 2, 3]));
+
+    let (_, _, projection) = read_dimacs_with_projection(&mut Cursor::new("p cnf 1 1\n1 0\n"));
+
+    assert_eq!(projection, None);
+}
+
+#[test]
+fn c_ind_needs_the_whole_token() {
+    let dimacs = "c indeed a comment\nc\tind 2 0\np cnf 2 1\n1 2 0\n";
+    let (_, _, projection) = read_dimacs_with_projection(&mut Cursor::new(dimacs));
+
+    assert_eq!(projection, Some(vec![1]));
+}
+
+#[test]
+#[should_panic(expected = "Invalid variable 'x'")]
+fn c_ind_rejects_malformed_variables() {
+    read_dimacs_with_projection(&mut Cursor::new("c ind 1 x 0\np cnf 2 1\n1 2 0\n"));
+}
+
+#[test]
+#[should_panic(expected = "exceeds the 2 variables")]
+fn c_ind_rejects_variables_beyond_the_formula() {
+    read_dimacs_with_projection(&mut Cursor::new("p cnf 2 1\n1 2 0\nc ind 3 0\n"));
+}
@@ -0,0 +1,668 @@
+use num::rational::Ratio;
+use rand::{thread_rng, Rng};
+use std::panic::{self, AssertUnwindSafe};
+
+use ail_project::cdcl::mincut::CutMinimal;
+use ail_project::cdcl::propagation::{ConflictAnalysis, Trail};
+use ail_project::cdcl::Formula;
+use ail_project::sk1flow::certificate::verify;
+use ail_project::sk1flow::{
+    Capacity, Dinic, FlowError, FlowGraph, FlowGraphBuilder, MaxFlow, PushRelabel, SK1Flow,
+    VertexId, VertexNetwork,
+};
+
+const SOURCE: usize = 0;
+const SINK: usize = 1;
+
+type Edges = Vec<(usize, usize, u64)>;
+
+fn random_network(rng: &mut impl Rng) -> (usize, Edges) {
+    let n = rng.gen_range(2..30);
+    let m = rng.gen_range(0..4 * n);
+
+    let edges = (0..m)
+        .map(|_| {
+            (
+                rng.gen_range(0..n),
+                rng.gen_range(0..n),
+                rng.gen_range(0..20),
+            )
+        })
+        .collect();
+
+    (n, edges)
+}
+
+fn build<F: MaxFlow<u64>>(n: usize, edges: &Edges) -> F {
+    let infinity = edges.iter().map(|edge| edge.2).sum::<u64>() + 1;
+
+    let mut network = F::default();
+
+    for v in 0..n {
+        network.add_vertex(if v == SOURCE { infinity } else { 0 });
+    }
+
+    for &(from, to, capacity) in edges {
+        network.add_edge(from, to, capacity);
+    }
+
+    network
+}
+
+fn cut_value(edges: &Edges, sink_side: &[bool]) -> u64 {
+    assert!(sink_side[SINK] && !sink_side[SOURCE]);
+
+    edges
+        .iter()
+        .filter(|(from, to, _)| !sink_side[*from] && sink_side[*to])
+        .map(|edge| edge.2)
+        .sum()
+}
+
+/// Flow value and both extreme minimum cuts, checked against the flow value.
+fn solve<F: MaxFlow<u64>>(n: usize, edges: &Edges) -> (u64, Vec<bool>, Vec<bool>) {
+    let mut network = build::<F>(n, edges);
+
+    let value = network.flow(SINK);
+    let sink_cut = network.cut(SINK);
+    let source_cut = network.source_cut(SOURCE);
+
+    assert_eq!(cut_value(edges, &sink_cut), value);
+    assert_eq!(cut_value(edges, &source_cut), value);
+
+    (value, sink_cut, source_cut)
+}
+
+#[test]
+fn backends_agree() {
+    let mut rng = thread_rng();
+
+    for _ in 0..1e4 as usize {
+        let (n, edges) = random_network(&mut rng);
+
+        let expected = solve::<SK1Flow<u64>>(n, &edges);
+
+        assert_eq!(solve::<Dinic<u64>>(n, &edges), expected);
+        assert_eq!(solve::<PushRelabel<u64>>(n, &edges), expected);
+    }
+}
+
+#[test]
+fn backends_agree_on_layered_networks() {
+    let mut rng = thread_rng();
+
+    for _ in 0..1e3 as usize {
+        // long chains of layers, like implication graphs
+        let width = rng.gen_range(1..5);
+        let depth = rng.gen_range(1..40);
+        let n = 2 + width * depth;
+        let vertex = |layer: usize, index: usize| 2 + layer * width + index;
+
+        let mut edges = vec![];
+
+        for index in 0..width {
+            edges.push((SOURCE, vertex(0, index), rng.gen_range(1..20)));
+            edges.push((vertex(depth - 1, index), SINK, rng.gen_range(1..20)));
+        }
+
+        for layer in 1..depth {
+            for _ in 0..2 * width {
+                let from = vertex(layer - 1, rng.gen_range(0..width));
+                let to = vertex(layer, rng.gen_range(0..width));
+
+                edges.push((from, to, rng.gen_range(0..20)));
+            }
+        }
+
+        let expected = solve::<SK1Flow<u64>>(n, &edges);
+
+        assert_eq!(solve::<Dinic<u64>>(n, &edges), expected);
+        assert_eq!(solve::<PushRelabel<u64>>(n, &edges), expected);
+    }
+}
+
+#[test]
+fn textbook_network() {
+    let mut builder = FlowGraphBuilder::new();
+    let [s, v1, v2, v3, v4, t] = [(); 6].map(|_| builder.add_vertex());
+
+    builder.add_edge(s, v1, 16u64);
+    builder.add_edge(s, v2, 13);
+    builder.add_edge(v2, v1, 4);
+    let v1v3 = builder.add_edge(v1, v3, 12);
+    builder.add_edge(v3, v2, 9);
+    builder.add_edge(v2, v4, 14);
+    let v4v3 = builder.add_edge(v4, v3, 7);
+    let v3t = builder.add_edge(v3, t, 20);
+    let v4t = builder.add_edge(v4, t, 4);
+
+    let mut graph = builder.build();
+
+    assert_eq!(graph.flow_value(), None);
+    assert_eq!(graph.max_flow(s, t), 23);
+    assert_eq!(graph.flow_value(), Some(&23));
+
+    let cut = graph.min_cut();
+
+    assert_eq!(cut.edges(), &[v1v3, v4v3, v4t]);
+    assert!(cut.is_source_side(v4) && cut.is_sink_side(v3));
+
+    for edge in cut.edges() {
+        assert_eq!(&graph.edge_flow(*edge), graph.capacity(*edge));
+    }
+
+    assert_eq!(graph.endpoints(v3t), (v3, t));
+    assert_eq!(graph.max_flow(s, v4), 14);
+}
+
+/// Checks that the edge flows of the last flow are a flow of its value, and the cuts minimum.
+fn check_flow_graph<F: MaxFlow<u64>>(graph: &FlowGraph<u64, F>, s: VertexId, t: VertexId) {
+    let value = *graph.flow_value().unwrap();
+    let mut balance = vec![0i64; graph.vertex_count()];
+    let mut inflow = vec![0; graph.vertex_count()];
+
+    for edge in graph.edges() {
+        let flow = graph.edge_flow(edge);
+        let (from, to) = graph.endpoints(edge);
+
+        assert!(flow <= *graph.capacity(edge));
+
+        balance[from.index()] -= flow as i64;
+        balance[to.index()] += flow as i64;
+        inflow[to.index()] += flow;
+    }
+
+    for v in graph.vertices().filter(|&v| v != s && v != t) {
+        if let Some(capacity) = graph.vertex_capacity(v) {
+            assert_eq!(graph.vertex_flow(v), Some(inflow[v.index()]));
+            assert!(inflow[v.index()] <= *capacity);
+        }
+    }
+
+    for v in graph.vertices() {
+        let expected = match v {
+            _ if v == s => -(value as i64),
+            _ if v == t => value as i64,
+            _ => 0,
+        };
+
+        assert_eq!(balance[v.index()], expected);
+    }
+
+    let sink_cut = graph.min_cut();
+    let source_cut = graph.source_min_cut();
+
+    for cut in [&sink_cut, &source_cut] {
+        assert!(cut.is_source_side(s) && cut.is_sink_side(t));
+
+        let capacity: u64 = cut.edges().iter().map(|edge| graph.capacity(*edge)).sum();
+        let vertex_capacity: u64 = cut
+            .vertices()
+            .iter()
+            .map(|v| graph.vertex_capacity(*v).unwrap())
+            .sum();
+
+        assert_eq!(capacity + vertex_capacity, value);
+
+        for v in cut.vertices() {
+            assert!(cut.is_source_side(*v) && *v != s && *v != t);
+        }
+    }
+
+    for v in graph.vertices() {
+        assert!(!sink_cut.is_sink_side(v) || source_cut.is_sink_side(v));
+    }
+}
+
+fn random_flow_graph<F: MaxFlow<u64>>(n: usize, edges: &Edges) -> FlowGraph<u64, F> {
+    let mut builder = FlowGraphBuilder::new();
+    let vertices: Vec<_> = (0..n).map(|_| builder.add_vertex()).collect();
+
+    for &(from, to, capacity) in edges {
+        builder.add_edge(vertices[from], vertices[to], capacity);
+    }
+
+    builder.build_with()
+}
+
+#[test]
+fn flow_graph_queries() {
+    let mut rng = thread_rng();
+
+    for _ in 0..1e3 as usize {
+        let (n, edges) = random_network(&mut rng);
+        let mut sk1 = random_flow_graph::<SK1Flow<u64>>(n, &edges);
+        let mut dinic = random_flow_graph::<Dinic<u64>>(n, &edges);
+        let mut push_relabel = random_flow_graph::<PushRelabel<u64>>(n, &edges);
+
+        let source = rng.gen_range(0..n);
+        let sink = (source + rng.gen_range(1..n)) % n;
+        let s = sk1.vertices().nth(source).unwrap();
+        let t = sk1.vertices().nth(sink).unwrap();
+
+        let value = sk1.max_flow(s, t);
+
+        assert_eq!(dinic.max_flow(s, t), value);
+        assert_eq!(push_relabel.max_flow(s, t), value);
+
+        check_flow_graph(&sk1, s, t);
+        check_flow_graph(&dinic, s, t);
+        check_flow_graph(&push_relabel, s, t);
+
+        assert_eq!(dinic.min_cut(), sk1.min_cut());
+        assert_eq!(push_relabel.source_min_cut(), sk1.source_min_cut());
+    }
+}
+
+#[test]
+fn infinite_source_edges() {
+    fn test<F: MaxFlow<u64>>() {
+        let mut builder = FlowGraphBuilder::new();
+        let [s, t, a, b] = [(); 4].map(|_| builder.add_vertex());
+
+        builder.add_edge(s, a, u64::MAX);
+        builder.add_edge(s, b, u64::MAX);
+        builder.add_edge(a, b, u64::MAX);
+        builder.add_edge(a, t, 3);
+        builder.add_edge(b, t, 4);
+
+        let mut graph: FlowGraph<u64, F> = builder.build_with();
+
+        assert_eq!(graph.max_flow(s, t), 7);
+        check_flow_graph(&graph, s, t);
+
+        // a flow of exactly the largest capacity still fits, with a matching cut
+        let mut builder = FlowGraphBuilder::new();
+        let [s, t, a, b] = [(); 4].map(|_| builder.add_vertex());
+
+        builder.add_edge(s, a, u64::MAX);
+        builder.add_edge(s, b, u64::MAX);
+        builder.add_edge(a, t, u64::MAX - 1);
+        builder.add_edge(b, t, 1);
+
+        let mut graph: FlowGraph<u64, F> = builder.build_with();
+
+        assert_eq!(graph.max_flow(s, t), u64::MAX);
+
+        for cut in [graph.min_cut(), graph.source_min_cut()] {
+            assert!(cut.is_source_side(s) && cut.is_sink_side(t));
+
+            let capacity: u128 = cut
+                .edges()
+                .iter()
+                .map(|edge| *graph.capacity(*edge) as u128)
+                .sum();
+
+            assert_eq!(capacity, u64::MAX as u128);
+        }
+
+        // a flow above the largest capacity is rejected
+        let mut builder = FlowGraphBuilder::new();
+        let [s, t, a, b] = [(); 4].map(|_| builder.add_vertex());
+
+        builder.add_edge(s, a, u64::MAX);
+        builder.add_edge(s, b, u64::MAX);
+        builder.add_edge(a, t, u64::MAX);
+        builder.add_edge(b, t, u64::MAX);
+
+        let mut graph: FlowGraph<u64, F> = builder.build_with();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| graph.max_flow(s, t)));
+
+        assert!(result.is_err());
+    }
+
+    test::<SK1Flow<u64>>();
+    test::<Dinic<u64>>();
+    test::<PushRelabel<u64>>();
+}
+
+#[test]
+fn vertex_capacities() {
+    // two disjoint paths through a bottleneck of capacity 3, and a direct edge of capacity 2
+    let mut builder = FlowGraphBuilder::new();
+    let s = builder.add_vertex();
+    let t = builder.add_vertex();
+    let a = builder.add_vertex_with_capacity(10u64);
+    let b = builder.add_vertex_with_capacity(10);
+    let bottleneck = builder.add_vertex_with_capacity(3);
+
+    builder.add_edge(s, a, 10);
+    builder.add_edge(s, b, 10);
+    builder.add_edge(a, bottleneck, 10);
+    builder.add_edge(b, bottleneck, 10);
+    builder.add_edge(bottleneck, t, 10);
+    let st = builder.add_edge(s, t, 2);
+
+    let mut graph = builder.build();
+
+    assert_eq!(graph.max_flow(s, t), 5);
+    assert_eq!(graph.vertex_flow(bottleneck), Some(3));
+    assert_eq!(graph.vertex_flow(s), None);
+
+    for cut in [graph.min_cut(), graph.source_min_cut()] {
+        assert_eq!(cut.vertices(), &[bottleneck]);
+        assert_eq!(cut.edges(), &[st]);
+    }
+
+    check_flow_graph(&graph, s, t);
+}
+
+/// The graph with capacitated vertices, and the same graph with every such vertex split by hand
+/// into an in-vertex, an out-vertex and an edge between them, with the out-vertices numbered
+/// after the other vertices and returned.
+fn vertex_flow_graphs<F: MaxFlow<u64>>(
+    capacities: &[Option<u64>],
+    edges: &Edges,
+) -> (FlowGraph<u64, F>, FlowGraph<u64, F>, Vec<VertexId>) {
+    let mut builder = FlowGraphBuilder::new();
+    let mut split = FlowGraphBuilder::new();
+
+    let vertices: Vec<_> = capacities
+        .iter()
+        .map(|capacity| match capacity {
+            None => builder.add_vertex(),
+            Some(capacity) => builder.add_vertex_with_capacity(*capacity),
+        })
+        .collect();
+    let ins: Vec<_> = capacities.iter().map(|_| split.add_vertex()).collect();
+    let outs: Vec<_> = capacities
+        .iter()
+        .zip(&ins)
+        .map(|(capacity, &in_id)| match capacity {
+            None => in_id,
+            Some(capacity) => {
+                let out_id = split.add_vertex();
+                split.add_edge(in_id, out_id, *capacity);
+
+                out_id
+            }
+        })
+        .collect();
+
+    for &(from, to, capacity) in edges {
+        builder.add_edge(vertices[from], vertices[to], capacity);
+        split.add_edge(outs[from], ins[to], capacity);
+    }
+
+    (builder.build_with(), split.build_with(), outs)
+}
+
+fn check_vertex_capacities<F: MaxFlow<u64>>(
+    capacities: &[Option<u64>],
+    edges: &Edges,
+    source: usize,
+    sink: usize,
+) -> u64 {
+    let (mut graph, mut split, outs) = vertex_flow_graphs::<F>(capacities, edges);
+
+    let s = graph.vertices().nth(source).unwrap();
+    let t = graph.vertices().nth(sink).unwrap();
+
+    let value = graph.max_flow(s, t);
+
+    // the flow leaves the source after its capacity and enters the sink before it
+    assert_eq!(split.max_flow(outs[source], t), value);
+
+    check_flow_graph(&graph, s, t);
+
+    // the cut vertices are the split edges in the cut of the split graph
+    let split_cut = split.min_cut();
+    let mut cut_vertices: Vec<_> = split_cut
+        .edges()
+        .iter()
+        .filter_map(|edge| {
+            let (from, to) = split.endpoints(*edge);
+
+            (from.index() < capacities.len() && to.index() >= capacities.len()).then_some(from)
+        })
+        .collect();
+    cut_vertices.sort();
+
+    assert_eq!(graph.min_cut().vertices(), &cut_vertices[..]);
+
+    value
+}
+
+#[test]
+fn vertex_capacities_match_split_graph() {
+    let mut rng = thread_rng();
+
+    for _ in 0..1e3 as usize {
+        let (n, edges) = random_network(&mut rng);
+        let capacities: Vec<_> = (0..n)
+            .map(|_| rng.gen_bool(0.5).then(|| rng.gen_range(0..20)))
+            .collect();
+
+        let source = rng.gen_range(0..n);
+        let sink = (source + rng.gen_range(1..n)) % n;
+
+        let value = check_vertex_capacities::<SK1Flow<u64>>(&capacities, &edges, source, sink);
+
+        assert_eq!(
+            check_vertex_capacities::<Dinic<u64>>(&capacities, &edges, source, sink),
+            value
+        );
+        assert_eq!(
+            check_vertex_capacities::<PushRelabel<u64>>(&capacities, &edges, source, sink),
+            value
+        );
+    }
+}
+
+#[test]
+fn min_cut_network_splits_literals_with_many_parents() {
+    let formula: Formula = vec![vec![0, 1], vec![!1, 2], vec![0, !2, 3]];
+    let trail = Trail::new(4, formula.len());
+    let mut analysis = CutMinimal::from_formula(4, &formula);
+
+    analysis.decide_literal(&formula, &trail, !0);
+    analysis.propagate_literal(&formula, &trail, 1, 0);
+    analysis.propagate_literal(&formula, &trail, 2, 1);
+    analysis.propagate_literal(&formula, &trail, 3, 2);
+
+    let network = analysis.network();
+
+    // the source and the sink, then the literals, only the last one has two parents
+    assert_eq!(network.vertex_count(), 2 + 4);
+    assert_eq!(network.network().vertex_count(), 2 + 4 + 1);
+    assert!((2..5).all(|v| network.is_single(v)) && !network.is_single(5));
+    // the source to the decision, the reasons and one inner edge, each with its reverse
+    assert_eq!(network.edge_count(), 2 * (1 + 4 + 1));
+
+    // a vertex added with its edge in is cut on that edge
+    let mut network = VertexNetwork::<u64>::default();
+    let [s, t] = [(); 2].map(|_| network.add_vertex(None));
+    let a = network.add_vertex_from(s, 1);
+    let b = network.add_vertex(Some(3));
+    let c = network.add_vertex(None);
+
+    network.add_edge(s, b, 5);
+    network.add_edge(a, c, 5);
+    network.add_edge(b, c, 5);
+    network.add_edge(c, t, 5);
+
+    assert_eq!(network.vertex_count(), 5);
+    assert_eq!(network.network().vertex_count(), 6);
+    assert_eq!(network.edge_count(), 2 * (1 + 1 + 4));
+
+    network.set_excess(s, 10);
+
+    assert_eq!(network.flow(t), 4);
+    assert_eq!(network.vertex_cut(t), [false, false, true, true, false]);
+    assert_eq!(
+        network.in_edges(a).collect::<Vec<_>>(),
+        [(network.inner_edge(a).unwrap(), s)]
+    );
+
+    // as if it were split, a vertex without flow or capacity is cut even if its parent reaches
+    // the sink
+    for single in [false, true] {
+        let mut network = VertexNetwork::<u64>::default();
+        let [s, t, p] = [(); 3].map(|_| network.add_vertex(None));
+
+        let a = if single {
+            network.add_vertex_from(p, 0)
+        } else {
+            let a = network.add_vertex(Some(0));
+            network.add_edge(p, a, 10);
+            a
+        };
+
+        network.add_edge(s, p, 10);
+        network.add_edge(p, t, 5);
+        network.add_edge(a, t, 5);
+        network.set_excess(s, 1);
+
+        assert_eq!(network.flow(t), 1);
+        assert_eq!(network.vertex_cut(t), [false, false, false, true]);
+        assert_eq!(network.source_vertex_cut(s), [false, false, false, true]);
+    }
+}
+
+fn check_certificate<F: MaxFlow<u64>>(n: usize, edges: &Edges) {
+    let mut network = build::<F>(n, edges);
+    let before = network.network().clone();
+
+    let value = network.flow(SINK);
+    let sink_cut = network.cut(SINK);
+    let decomposition = verify(&before, network.network(), SINK, &value, &sink_cut).unwrap();
+
+    for path in decomposition.paths.iter().chain(&decomposition.cycles) {
+        assert!(path.value > 0);
+
+        // consecutive edges
+        for pair in path.edges.windows(2) {
+            assert_eq!(
+                network.network().head(pair[0]),
+                network.network().tail(pair[1])
+            );
+        }
+    }
+
+    for cycle in &decomposition.cycles {
+        let first = cycle.edges.first().unwrap();
+        let last = cycle.edges.last().unwrap();
+
+        assert_eq!(
+            network.network().head(*last),
+            network.network().tail(*first)
+        );
+    }
+
+    assert_eq!(
+        verify(&before, network.network(), SINK, &(value + 1), &sink_cut),
+        Err(FlowError::Value)
+    );
+
+    // a cut around the sink alone is only minimum if the edges into the sink are saturated
+    let mut around_sink = vec![false; n];
+    around_sink[SINK] = true;
+
+    if around_sink != sink_cut {
+        assert!(verify(&before, network.network(), SINK, &value, &around_sink).is_err());
+    }
+
+    assert_eq!(network.certified_flow(SINK), Ok(0));
+}
+
+#[test]
+fn flows_are_certified() {
+    let mut rng = thread_rng();
+
+    for _ in 0..1e3 as usize {
+        let (n, edges) = random_network(&mut rng);
+
+        check_certificate::<SK1Flow<u64>>(n, &edges);
+        check_certificate::<Dinic<u64>>(n, &edges);
+        check_certificate::<PushRelabel<u64>>(n, &edges);
+
+        let mut network = build::<SK1Flow<u64>>(n, &edges);
+
+        assert_eq!(
+            network.certified_flow(SINK),
+            Ok(solve::<SK1Flow<u64>>(n, &edges).0)
+        );
+    }
+}
+
+#[test]
+fn corrupted_flows_are_rejected() {
+    let edges = vec![(SOURCE, 2, 3), (2, SINK, 2)];
+    let mut network = build::<SK1Flow<u64>>(3, &edges);
+    let before = network.network().clone();
+
+    let value = network.flow(SINK);
+    let sink_cut = network.cut(SINK);
+
+    assert_eq!(value, 2);
+    assert!(verify(&before, network.network(), SINK, &value, &sink_cut).is_ok());
+
+    // the edge into the middle vertex lost capacity its reverse did not gain
+    let mut capacity = network.clone();
+    capacity.network_mut().set_capacity(0, 2);
+
+    assert_eq!(
+        verify(&before, capacity.network(), SINK, &value, &sink_cut),
+        Err(FlowError::Capacity { edge: 0 })
+    );
+
+    // the middle vertex kept excess no edge brought in
+    let mut conservation = network.clone();
+    let excess = conservation.network().excess()[2];
+    conservation.network_mut().set_excess(2, excess + 1);
+
+    assert_eq!(
+        verify(&before, conservation.network(), SINK, &value, &sink_cut),
+        Err(FlowError::Conservation { vertex: 2 })
+    );
+}
+
+/// The network of `edges` with every capacity converted, and the maximum flow of `source` and
+/// `sink` computed.
+fn converted_flow_graph<T: Capacity>(
+    n: usize,
+    edges: &Edges,
+    source: usize,
+    sink: usize,
+    convert: impl Fn(u64) -> T,
+) -> (T, FlowGraph<T>) {
+    let mut builder = FlowGraphBuilder::new();
+    let vertices: Vec<_> = (0..n).map(|_| builder.add_vertex()).collect();
+
+    for &(from, to, capacity) in edges {
+        builder.add_edge(vertices[from], vertices[to], convert(capacity));
+    }
+
+    let mut graph = builder.build();
+    let value = graph.max_flow(vertices[source], vertices[sink]);
+
+    (value, graph)
+}
+
+#[test]
+fn float_and_rational_capacities() {
+    let mut rng = thread_rng();
+
+    for _ in 0..1e3 as usize {
+        let (n, edges) = random_network(&mut rng);
+        let source = rng.gen_range(0..n);
+        let sink = (source + rng.gen_range(1..n)) % n;
+
+        let (value, graph) = converted_flow_graph(n, &edges, source, sink, |c| c);
+        let cut = graph.min_cut();
+
+        // tenths are not exact in floating point
+        let (float_value, float_graph) =
+            converted_flow_graph(n, &edges, source, sink, |c| c as f64 / 10.0);
+
+        assert!((float_value * 10.0 - value as f64).abs() < 1e-6);
+        assert_eq!(float_graph.min_cut(), cut);
+
+        let (rational_value, rational_graph) =
+            converted_flow_graph(n, &edges, source, sink, |c| Ratio::new(c as i64, 3));
+
+        assert_eq!(rational_value, Ratio::new(value as i64, 3));
+        assert_eq!(rational_graph.min_cut(), cut);
+        assert_eq!(rational_graph.source_min_cut(), graph.source_min_cut());
+    }
+}
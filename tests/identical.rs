@@ -1,10 +1,22 @@
+use std::marker::PhantomData;
+
 use rand::{thread_rng, Rng};
 
 use ail_project::cdcl::decision::DecideFirstVariable;
 use ail_project::cdcl::first_uip::FirstUIP;
-use ail_project::cdcl::mincut::CutFirstUIP;
+use ail_project::cdcl::mincut::functions::{
+    ActivityFunction, AllUIPFunction, KthUIPFunction, LBDFunction, MinCutFunction, RelSatFunction,
+};
+use ail_project::cdcl::mincut::heuristic::{
+    CutSide, FlowNetwork, MinCutConflict, MinCutWeight, WeightHeuristic,
+};
+use ail_project::cdcl::mincut::level_weight::LevelWeight;
+use ail_project::cdcl::mincut::{CutAllUIP, CutFirstUIP, CutKthUIP, CutLBD, CutMinimal, CutRelSat};
+use ail_project::cdcl::propagation::{ConflictAnalysis, DecisionHeuristic, Trail};
+use ail_project::cdcl::uip::{AllUIP, KthUIP};
 use ail_project::*;
 use cdcl::*;
+use num::{BigRational, BigUint};
 
 #[test]
 fn identical_to_simple() {
@@ -93,3 +105,413 @@ fn identical_to_cut() {
         }
     }
 }
+
+/// Runs both analyses on every conflict and checks that they learn the same clause.
+struct Compare<A: ConflictAnalysis, B: ConflictAnalysis> {
+    reference: A,
+    tested: B,
+}
+
+impl<A: ConflictAnalysis, B: ConflictAnalysis> ConflictAnalysis for Compare<A, B> {
+    fn from_formula(n: usize, formula: &Formula) -> Self {
+        Self {
+            reference: A::from_formula(n, formula),
+            tested: B::from_formula(n, formula),
+        }
+    }
+
+    fn analyze_conflict(&mut self, formula: &Formula, trail: &Trail, conflict: Clause) -> Clause {
+        let mut reference = self
+            .reference
+            .analyze_conflict(formula, trail, conflict.clone());
+        let mut tested = self.tested.analyze_conflict(formula, trail, conflict);
+
+        reference.sort_unstable();
+        tested.sort_unstable();
+
+        assert_eq!(reference, tested);
+
+        reference
+    }
+
+    fn backtrack_and_add_clause(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        level: usize,
+        clause_id: usize,
+    ) {
+        self.reference
+            .backtrack_and_add_clause(formula, trail, level, clause_id);
+        self.tested
+            .backtrack_and_add_clause(formula, trail, level, clause_id);
+    }
+
+    fn backtrack(&mut self, formula: &Formula, trail: &Trail, level: usize) {
+        self.reference.backtrack(formula, trail, level);
+        self.tested.backtrack(formula, trail, level);
+    }
+
+    fn simplify(&mut self, formula: &Formula, trail: &Trail) {
+        self.reference.simplify(formula, trail);
+        self.tested.simplify(formula, trail);
+    }
+
+    fn propagate_literal(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        literal: Literal,
+        reason_id: usize,
+    ) {
+        self.reference
+            .propagate_literal(formula, trail, literal, reason_id);
+        self.tested
+            .propagate_literal(formula, trail, literal, reason_id);
+    }
+
+    fn decide_literal(&mut self, formula: &Formula, trail: &Trail, literal: Literal) {
+        self.reference.decide_literal(formula, trail, literal);
+        self.tested.decide_literal(formula, trail, literal);
+    }
+}
+
+fn test_identical_clauses<C: ConflictAnalysis>(make: impl Fn(usize, &Formula) -> C) {
+    let n: usize = 30;
+
+    let mut formula: Formula = vec![];
+
+    let mut rng = thread_rng();
+
+    for _ in 0..1e4 as usize {
+        let bnd = n as Literal;
+
+        formula.push((0..3).map(|_| rng.gen_range(-bnd..bnd)).collect());
+
+        let mut incremental = formula.clone();
+
+        let d = DecideFirstVariable::from_formula(n, &incremental);
+        let c = make(n, &incremental);
+
+        if !CDCL::new(n, &mut incremental, d, c).solve() {
+            formula.clear();
+        }
+    }
+}
+
+#[test]
+fn first_uip_identical_to_cut() {
+    test_identical_clauses(Compare::<FirstUIP, CutFirstUIP>::from_formula);
+}
+
+#[test]
+fn kth_uip_identical_to_cut() {
+    for k in [2, 3, KthUIP::ALL] {
+        test_identical_clauses(|n, formula| Compare {
+            reference: KthUIP::new(n, k),
+            tested: CutKthUIP::from_formula(n, formula)
+                .with_weight_heuristic(KthUIPFunction::new(k, 1)),
+        });
+    }
+
+    test_identical_clauses(|n, formula| Compare {
+        reference: KthUIP::new(n, KthUIP::ALL),
+        tested: CutAllUIP::from_formula(n, formula),
+    });
+}
+
+/// Feng & Bacchus's all-UIP keeps the literals of a level whose UIP would bring in a new level,
+/// the cut replaces every level. Both learn the same clause when the cut brings in no new level,
+/// otherwise all-UIP stays on the levels of the first UIP clause.
+struct CompareAllUIP {
+    first_uip: FirstUIP,
+    all_uip: AllUIP,
+    cut: CutAllUIP,
+}
+
+impl ConflictAnalysis for CompareAllUIP {
+    fn from_formula(n: usize, formula: &Formula) -> Self {
+        Self {
+            first_uip: FirstUIP::new(n),
+            all_uip: AllUIP::new(n),
+            cut: CutAllUIP::from_formula(n, formula),
+        }
+    }
+
+    fn analyze_conflict(&mut self, formula: &Formula, trail: &Trail, conflict: Clause) -> Clause {
+        let levels = |clause: &Clause| {
+            let mut levels: Vec<_> = clause
+                .iter()
+                .map(|&literal| trail.assignment[variable_name(literal)].decision_level())
+                .filter(|&level| level != 0)
+                .collect();
+
+            levels.sort_unstable();
+            levels.dedup();
+            levels
+        };
+
+        let first_uip = self
+            .first_uip
+            .analyze_conflict(formula, trail, conflict.clone());
+        let mut all_uip = self
+            .all_uip
+            .analyze_conflict(formula, trail, conflict.clone());
+        let mut cut = self.cut.analyze_conflict(formula, trail, conflict);
+
+        all_uip.sort_unstable();
+        cut.sort_unstable();
+
+        if levels(&cut) == levels(&first_uip) {
+            assert_eq!(all_uip, cut);
+        } else {
+            assert_eq!(levels(&all_uip), levels(&first_uip));
+        }
+
+        all_uip
+    }
+
+    fn backtrack_and_add_clause(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        level: usize,
+        clause_id: usize,
+    ) {
+        self.cut
+            .backtrack_and_add_clause(formula, trail, level, clause_id);
+    }
+
+    fn backtrack(&mut self, formula: &Formula, trail: &Trail, level: usize) {
+        self.cut.backtrack(formula, trail, level);
+    }
+
+    fn simplify(&mut self, formula: &Formula, trail: &Trail) {
+        self.cut.simplify(formula, trail);
+    }
+
+    fn propagate_literal(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        literal: Literal,
+        reason_id: usize,
+    ) {
+        self.cut
+            .propagate_literal(formula, trail, literal, reason_id);
+    }
+
+    fn decide_literal(&mut self, formula: &Formula, trail: &Trail, literal: Literal) {
+        self.cut.decide_literal(formula, trail, literal);
+    }
+}
+
+#[test]
+fn all_uip_identical_to_cut() {
+    test_identical_clauses(CompareAllUIP::from_formula);
+}
+
+/// The LBD cut only approximates the minimum LBD: its level gates bound it by the LBD of the
+/// first UIP clause, which is one of the cuts it weighs.
+struct BoundLBD {
+    first_uip: FirstUIP,
+    cut: CutLBD,
+}
+
+impl ConflictAnalysis for BoundLBD {
+    fn from_formula(n: usize, formula: &Formula) -> Self {
+        Self {
+            first_uip: FirstUIP::new(n),
+            cut: CutLBD::from_formula(n, formula),
+        }
+    }
+
+    fn analyze_conflict(&mut self, formula: &Formula, trail: &Trail, conflict: Clause) -> Clause {
+        let first_uip = self
+            .first_uip
+            .analyze_conflict(formula, trail, conflict.clone());
+        let cut = self.cut.analyze_conflict(formula, trail, conflict);
+
+        assert!(trail.lbd(&cut) <= trail.lbd(&first_uip));
+
+        first_uip
+    }
+
+    fn backtrack_and_add_clause(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        level: usize,
+        clause_id: usize,
+    ) {
+        self.cut
+            .backtrack_and_add_clause(formula, trail, level, clause_id);
+    }
+
+    fn backtrack(&mut self, formula: &Formula, trail: &Trail, level: usize) {
+        self.cut.backtrack(formula, trail, level);
+    }
+
+    fn simplify(&mut self, formula: &Formula, trail: &Trail) {
+        self.cut.simplify(formula, trail);
+    }
+
+    fn propagate_literal(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        literal: Literal,
+        reason_id: usize,
+    ) {
+        self.cut
+            .propagate_literal(formula, trail, literal, reason_id);
+    }
+
+    fn decide_literal(&mut self, formula: &Formula, trail: &Trail, literal: Literal) {
+        self.cut.decide_literal(formula, trail, literal);
+    }
+}
+
+#[test]
+fn lbd_cut_is_no_worse_than_first_uip() {
+    test_identical_clauses(BoundLBD::from_formula);
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_uip_aliases_identical_to_kth_uip() {
+    use ail_project::cdcl::mincut::{CutSatAllUIP, CutSecondUIP, CutThirdUIP};
+
+    test_identical_clauses(|n, formula| Compare {
+        reference: KthUIP::new(n, 2),
+        tested: CutSecondUIP::from_formula(n, formula),
+    });
+    test_identical_clauses(|n, formula| Compare {
+        reference: KthUIP::new(n, 3),
+        tested: CutThirdUIP::from_formula(n, formula),
+    });
+    test_identical_clauses(|n, formula| Compare {
+        reference: KthUIP::new(n, KthUIP::ALL),
+        tested: CutSatAllUIP::from_formula(n, formula),
+    });
+}
+
+#[test]
+fn real_and_rational_kth_uip_identical_to_trail() {
+    for k in [2, 3, KthUIP::ALL] {
+        test_identical_clauses(|n, formula| Compare {
+            reference: KthUIP::new(n, k),
+            tested: MinCutConflict::<BigRational, KthUIPFunction>::from_formula(n, formula)
+                .with_weight_heuristic(KthUIPFunction::new(k, 1)),
+        });
+    }
+
+    // deeper levels would weigh less than the epsilon of the floating-point weights
+    for k in [2, 3, 20] {
+        test_identical_clauses(|n, formula| Compare {
+            reference: KthUIP::new(n, k),
+            tested: MinCutConflict::<f64, KthUIPFunction>::from_formula(n, formula)
+                .with_weight_heuristic(KthUIPFunction::new(k, 1)),
+        });
+    }
+}
+
+#[test]
+fn level_weights_identical_to_big_integers() {
+    for cut_side in [CutSide::Sink, CutSide::Source] {
+        test_identical_clauses(|n, formula| Compare {
+            reference: MinCutConflict::<BigUint, AllUIPFunction>::from_formula(n, formula)
+                .with_cut_side(cut_side),
+            tested: MinCutConflict::<LevelWeight, AllUIPFunction>::from_formula(n, formula)
+                .with_cut_side(cut_side),
+        });
+    }
+}
+
+/// Builds a fresh analysis from the trail at every conflict, as if there were no incremental
+/// network to keep up to date.
+struct Rebuilt<C: ConflictAnalysis> {
+    n: usize,
+    analysis: PhantomData<C>,
+}
+
+impl<C: ConflictAnalysis> ConflictAnalysis for Rebuilt<C> {
+    fn from_formula(n: usize, _formula: &Formula) -> Self {
+        Self {
+            n,
+            analysis: PhantomData,
+        }
+    }
+
+    fn analyze_conflict(&mut self, formula: &Formula, trail: &Trail, conflict: Clause) -> Clause {
+        let mut analysis = C::from_formula(self.n, formula);
+
+        for &(variable, reason) in trail.levels.iter().flatten() {
+            let literal = trail.to_literal(variable);
+
+            match reason {
+                None => analysis.decide_literal(formula, trail, literal),
+                Some(reason_id) => analysis.propagate_literal(formula, trail, literal, reason_id),
+            }
+        }
+
+        analysis.analyze_conflict(formula, trail, conflict)
+    }
+
+    fn backtrack_and_add_clause(
+        &mut self,
+        _formula: &Formula,
+        _trail: &Trail,
+        _level: usize,
+        _clause_id: usize,
+    ) {
+    }
+
+    fn propagate_literal(
+        &mut self,
+        _formula: &Formula,
+        _trail: &Trail,
+        _literal: Literal,
+        _reason_id: usize,
+    ) {
+    }
+
+    fn decide_literal(&mut self, _formula: &Formula, _trail: &Trail, _literal: Literal) {}
+}
+
+#[test]
+fn incremental_identical_to_rebuilt() {
+    fn test<C: ConflictAnalysis>() {
+        test_identical_clauses(|n, formula| Compare {
+            reference: Rebuilt::<C>::from_formula(n, formula),
+            tested: C::from_formula(n, formula),
+        });
+    }
+
+    test::<CutMinimal>();
+    test::<CutFirstUIP>();
+    test::<CutAllUIP>();
+    test::<CutRelSat>();
+    test::<CutLBD>();
+}
+
+fn test_cone_identical_to_full<W: MinCutWeight, T: WeightHeuristic<W>>() {
+    for flow_network in [FlowNetwork::Cone, FlowNetwork::Incremental] {
+        test_identical_clauses(|n, formula| Compare {
+            reference: MinCutConflict::<W, T>::from_formula(n, formula)
+                .with_flow_network(FlowNetwork::Full),
+            tested: MinCutConflict::<W, T>::from_formula(n, formula)
+                .with_flow_network(flow_network),
+        });
+    }
+}
+
+#[test]
+fn cone_identical_to_full() {
+    test_cone_identical_to_full::<usize, MinCutFunction>();
+    test_cone_identical_to_full::<usize, KthUIPFunction>();
+    test_cone_identical_to_full::<BigUint, AllUIPFunction>();
+    test_cone_identical_to_full::<usize, RelSatFunction>();
+    test_cone_identical_to_full::<usize, LBDFunction>();
+    test_cone_identical_to_full::<usize, ActivityFunction>();
+}
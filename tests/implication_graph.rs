@@ -0,0 +1,152 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use ail_project::cdcl::decision::DecideFirstVariable;
+use ail_project::cdcl::dynamic::{
+    conflict_analysis_factory, decision_heuristic_factory, ConflictAnalysisFactory,
+};
+use ail_project::cdcl::first_uip::FirstUIP;
+use ail_project::cdcl::implication_graph::{DumpGraph, ImplicationGraph};
+use ail_project::cdcl::mincut::{CutAllUIP, CutMinimal};
+use ail_project::cdcl::propagation::DynConflictAnalysis;
+use ail_project::cdcl::*;
+use rand::{thread_rng, Rng};
+
+/// Solves `formula` and returns the graph of its `conflict`-th conflict, if it has that many.
+fn dump_graph(
+    analysis: &dyn ConflictAnalysisFactory,
+    n: usize,
+    formula: &Formula,
+    conflict: usize,
+) -> Option<ImplicationGraph> {
+    let graph = Rc::new(RefCell::new(None));
+
+    let factory = |n: usize, formula: &Formula| -> Box<dyn DynConflictAnalysis> {
+        let dumped = graph.clone();
+
+        Box::new(DumpGraph::new(
+            analysis.create(n, formula),
+            conflict,
+            move |graph: &ImplicationGraph| *dumped.borrow_mut() = Some(graph.clone()),
+        ))
+    };
+
+    cdcl_solve_dyn(
+        n,
+        &mut formula.clone(),
+        decision_heuristic_factory::<DecideFirstVariable>().as_ref(),
+        &factory,
+    );
+
+    graph.take()
+}
+
+#[test]
+fn first_uip_graph() {
+    // deciding 1 implies 3 and 4, which conflict
+    let formula = vec![
+        vec![0, 1],
+        vec![-1, 2],
+        vec![-1, -3, 3],
+        vec![-4, -2],
+        vec![-3, -4],
+        vec![1, 2],
+    ];
+
+    let graph = dump_graph(
+        conflict_analysis_factory::<FirstUIP>().as_ref(),
+        4,
+        &formula,
+        1,
+    )
+    .unwrap();
+
+    let literals: Vec<_> = graph.vertices.iter().map(|vertex| vertex.literal).collect();
+
+    assert_eq!(literals, [0, 2, 3]);
+    assert!(graph.vertices[0].is_decision);
+    assert!(graph.vertices.iter().all(|vertex| vertex.level == 1));
+    assert!(graph.vertices.iter().all(|vertex| vertex.weight.is_none()));
+    assert_eq!(graph.edges, [(0, 1), (0, 2), (1, 2)]);
+    assert_eq!(graph.learned, [-1]);
+    assert_eq!(
+        (0..3).map(|index| graph.is_cut(index)).collect::<Vec<_>>(),
+        [true, false, false]
+    );
+
+    let dot = graph.to_dot();
+
+    assert!(dot.contains("v0 [label=\"1\", shape=box, color=red, penwidth=2];"));
+    assert!(dot.contains("v1 -> conflict;") && dot.contains("v2 -> conflict;"));
+    assert!(!dot.contains("v0 -> conflict;"));
+
+    let json = graph.to_json();
+
+    assert!(json.contains(
+        "{\"id\": 0, \"literal\": 1, \"level\": 1, \"decision\": true, \"weight\": null, \
+         \"cut\": true}"
+    ));
+    assert!(json.contains("\"edges\": [[0, 1], [0, 2], [1, 2]]"));
+    assert!(json.contains("\"conflict\": [-3, -4]"));
+    assert!(json.contains("\"learned\": [-1]"));
+}
+
+#[test]
+fn graphs_match_learned_clauses() {
+    let n = 30;
+    let analyses = [
+        (conflict_analysis_factory::<FirstUIP>(), false),
+        (conflict_analysis_factory::<CutMinimal>(), true),
+        (conflict_analysis_factory::<CutAllUIP>(), true),
+    ];
+
+    let mut rng = thread_rng();
+    let mut graphs = 0;
+
+    for _ in 0..200 {
+        let bnd = n as Literal;
+        let formula: Formula = (0..130)
+            .map(|_| (0..3).map(|_| rng.gen_range(-bnd..bnd)).collect())
+            .collect();
+        let conflict = rng.gen_range(1..10);
+
+        for (analysis, weighted) in &analyses {
+            let Some(graph) = dump_graph(analysis.as_ref(), n, &formula, conflict) else {
+                continue;
+            };
+
+            graphs += 1;
+
+            let vertex = |literal: Literal| {
+                graph
+                    .vertices
+                    .iter()
+                    .position(|vertex| vertex.literal == literal)
+            };
+
+            // the cut and the conflict are made of falsified literals of the graph
+            for literal in graph.learned.iter().chain(&graph.conflict) {
+                assert!(vertex(!*literal).is_some());
+            }
+
+            assert_eq!(
+                (0..graph.vertices.len())
+                    .filter(|&index| graph.is_cut(index))
+                    .count(),
+                graph.learned.len()
+            );
+
+            for &(from, to) in &graph.edges {
+                assert!(from < to);
+                assert!(!graph.vertices[to].is_decision);
+                assert!(graph.vertices[from].level <= graph.vertices[to].level);
+            }
+
+            for vertex in &graph.vertices {
+                assert_eq!(vertex.weight.is_some(), *weighted);
+            }
+        }
+    }
+
+    assert!(graphs > 0);
+}
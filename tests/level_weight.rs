@@ -0,0 +1,51 @@
+use ail_project::cdcl::mincut::level_weight::LevelWeight;
+use num::{BigUint, One, Zero};
+use rand::{thread_rng, Rng};
+
+/// A random sum of level weights, as both types.
+fn random_weight(rng: &mut impl Rng, max_level: usize) -> (LevelWeight, BigUint) {
+    let mut weight = LevelWeight::zero();
+    let mut expected = BigUint::zero();
+
+    for _ in 0..rng.gen_range(0..20) {
+        let level = rng.gen_range(0..max_level);
+
+        weight += &LevelWeight::level(level);
+        expected += BigUint::one() << level;
+    }
+
+    (weight, expected)
+}
+
+#[test]
+fn level_weights_match_big_integers() {
+    let mut rng = thread_rng();
+
+    for _ in 0..1e4 as usize {
+        // deep levels spill out of the inline words
+        let max_level = if rng.gen_bool(0.5) { 200 } else { 600 };
+        let (mut a, mut expected_a) = random_weight(&mut rng, max_level);
+        let (b, expected_b) = random_weight(&mut rng, max_level);
+
+        assert_eq!(a.cmp(&b), expected_a.cmp(&expected_b));
+        assert_eq!(a == b, expected_a == expected_b);
+
+        let sum = a.clone() + b.clone();
+        let mut difference = sum.clone();
+        difference -= &b;
+
+        assert_eq!(difference, a);
+        assert_eq!(sum.cmp(&a), (&expected_a + &expected_b).cmp(&expected_a));
+
+        if a >= b {
+            a -= &b;
+            expected_a -= &expected_b;
+
+            assert_eq!(a.is_zero(), expected_a.is_zero());
+            assert_eq!(
+                a.cmp(&LevelWeight::level(300)),
+                expected_a.cmp(&(BigUint::one() << 300))
+            );
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+use ail_project::cdcl::decision::DecideFirstVariable;
+use ail_project::cdcl::first_uip::FirstUIP;
+use ail_project::cdcl::mincut::CutMinimal;
+use ail_project::cdcl::propagation::{ConflictAnalysis, DecisionHeuristic};
+use ail_project::cdcl::*;
+
+/// `holes + 1` pigeons in `holes` holes, unsatisfiable after many conflicts.
+fn pigeonhole(holes: usize) -> (usize, Formula) {
+    let variable = |pigeon: usize, hole: usize| (pigeon * holes + hole) as Literal;
+    let mut formula: Formula = (0..=holes)
+        .map(|pigeon| (0..holes).map(|hole| variable(pigeon, hole)).collect())
+        .collect();
+
+    for hole in 0..holes {
+        for a in 0..=holes {
+            for b in a + 1..=holes {
+                formula.push(vec![!variable(a, hole), !variable(b, hole)]);
+            }
+        }
+    }
+
+    ((holes + 1) * holes, formula)
+}
+
+fn solver(n: usize, formula: &mut Formula) -> CDCL<'_, DecideFirstVariable, FirstUIP> {
+    let d = DecideFirstVariable::from_formula(n, formula);
+    let c = FirstUIP::from_formula(n, formula);
+
+    CDCL::new(n, formula, d, c)
+}
+
+#[test]
+fn progress_reports_every_interval() {
+    let (n, mut formula) = pigeonhole(5);
+    let clauses = formula.len();
+    let events = Rc::new(RefCell::new(vec![]));
+
+    let reported = events.clone();
+    let mut cdcl = solver(n, &mut formula).with_progress(3, move |event: &ProgressEvent| {
+        reported.borrow_mut().push(*event)
+    });
+
+    assert_eq!(cdcl.search(), SolveResult::Unsatisfiable);
+
+    let conflicts = cdcl.statistics().conflicts;
+    let events = events.take();
+
+    assert_eq!(events.len(), conflicts / 3);
+
+    for (index, event) in events.iter().enumerate() {
+        assert_eq!(event.conflicts, 3 * (index + 1));
+        assert!(event.decisions >= event.level);
+        // one clause per conflict, the last one is at level zero and learns none
+        assert_eq!(event.learned_clauses, event.conflicts);
+        assert!(event.trail_size <= n);
+    }
+
+    drop(cdcl);
+
+    assert_eq!(formula.len() - clauses, conflicts - 1);
+}
+
+#[test]
+fn terminate_stops_the_search() {
+    let (n, mut formula) = pigeonhole(6);
+
+    // set before the search, it stops at the first decision
+    let terminate = Arc::new(AtomicBool::new(true));
+    let mut cdcl = solver(n, &mut formula).with_terminate(terminate);
+
+    assert_eq!(cdcl.search(), SolveResult::Interrupted);
+    assert_eq!(cdcl.statistics().decisions, 0);
+
+    // set by another thread at the tenth conflict
+    let terminate = Arc::new(AtomicBool::new(false));
+    let (reached, conflict) = mpsc::channel();
+    let (stopped, stop) = mpsc::channel();
+
+    let handle = terminate.clone();
+    let stopper = thread::spawn(move || {
+        conflict.recv().unwrap();
+        handle.store(true, Ordering::Relaxed);
+        stopped.send(()).unwrap();
+    });
+
+    let (n, mut formula) = pigeonhole(6);
+    let mut cdcl = solver(n, &mut formula)
+        .with_terminate(terminate)
+        .with_progress(10, move |_: &ProgressEvent| {
+            // the other thread may be gone after the first report
+            if reached.send(()).is_ok() {
+                stop.recv().unwrap();
+            }
+        });
+
+    assert_eq!(cdcl.search(), SolveResult::Interrupted);
+    assert_eq!(cdcl.statistics().conflicts, 10);
+
+    stopper.join().unwrap();
+}
+
+#[test]
+fn terminate_with_min_cut() {
+    let (n, mut formula) = pigeonhole(5);
+    let terminate = Arc::new(AtomicBool::new(false));
+
+    let handle = terminate.clone();
+    let d = DecideFirstVariable::from_formula(n, &formula);
+    let c = CutMinimal::from_formula(n, &formula);
+    let mut cdcl = CDCL::new(n, &mut formula, d, c)
+        .with_terminate(terminate)
+        .with_progress(1, move |event: &ProgressEvent| {
+            if event.conflicts == 5 {
+                handle.store(true, Ordering::Relaxed);
+            }
+        });
+
+    assert_eq!(cdcl.search(), SolveResult::Interrupted);
+    assert_eq!(cdcl.statistics().conflicts, 5);
+}
@@ -1,16 +1,33 @@
 use ail_project::*;
 
 use ail_project::cdcl::decision::DecideFirstVariable;
+use ail_project::cdcl::dynamic::{conflict_analysis_factory, decision_heuristic_factory};
 use ail_project::cdcl::first_uip::FirstUIP;
+use ail_project::cdcl::hybrid::HybridMinCut;
+use ail_project::cdcl::mincut::functions::{
+    ActivityFunction, AllUIPFunction, KthUIPFunction, LBDFunction, MinCutFunction, RelSatFunction,
+};
+use ail_project::cdcl::mincut::heuristic::{
+    CutSide, MinCutConflict, MinCutWeight, WeightHeuristic,
+};
 use ail_project::cdcl::mincut::{
-    CutAllUIP, CutFirstUIP, CutMinimal, CutRelSat, CutSatAllUIP, CutSecondUIP, CutThirdUIP,
+    CutActivity, CutAllUIP, CutFirstUIP, CutLBD, CutMinimal, CutRealActivity, CutRelSat,
 };
-use ail_project::cdcl::propagation::{ConflictAnalysis, DecisionHeuristic};
+use ail_project::cdcl::propagation::{
+    ClauseType, ConflictAnalysis, DecisionHeuristic, Trail, VariableState,
+};
+use ail_project::cdcl::uip::{AllUIP, KthUIP};
+use ail_project::sk1flow::{Dinic, PushRelabel};
 use cdcl::*;
+use num::{BigRational, BigUint};
 use rand::{thread_rng, Rng};
 use varisat::{CnfFormula, Lit, Solver};
 
 fn test_random<D: DecisionHeuristic, C: ConflictAnalysis>() {
+    test_random_with(cdcl_solve::<D, C>)
+}
+
+fn test_random_with(solve: impl Fn(usize, &mut Formula) -> Option<Vec<bool>>) {
     let n: usize = 30;
 
     let mut formula: Formula = vec![];
@@ -24,7 +41,7 @@ fn test_random<D: DecisionHeuristic, C: ConflictAnalysis>() {
 
         let mut incremental = formula.clone();
 
-        let new = cdcl_solve::<D, C>(n, &mut incremental);
+        let new = solve(n, &mut incremental);
 
         let mut old = Solver::new();
         old.add_formula(&CnfFormula::from(formula.iter().map(|clause| {
@@ -58,6 +75,25 @@ fn basic_correctness() {
     test_random::<DecideFirstVariable, FirstUIP>()
 }
 
+#[test]
+fn trail_kth_uip_correctness() {
+    for k in [2, 3, KthUIP::ALL] {
+        test_random_with(|n, formula| {
+            let d = DecideFirstVariable::from_formula(n, formula);
+            let c = KthUIP::new(n, k);
+
+            let mut cdcl = CDCL::new(n, formula, d, c);
+
+            cdcl.solve().then(|| cdcl.get_assignment())
+        })
+    }
+}
+
+#[test]
+fn trail_all_uip_correctness() {
+    test_random::<DecideFirstVariable, AllUIP>()
+}
+
 #[test]
 fn first_uip_correctness() {
     test_random::<DecideFirstVariable, CutFirstUIP>()
@@ -68,14 +104,20 @@ fn mincut_correctness() {
     test_random::<DecideFirstVariable, CutMinimal>()
 }
 
+#[test]
+fn flow_backends_correctness() {
+    test_random::<DecideFirstVariable, MinCutConflict<usize, MinCutFunction, Dinic<usize>>>();
+    test_random::<DecideFirstVariable, MinCutConflict<usize, MinCutFunction, PushRelabel<usize>>>();
+}
+
 #[test]
 fn second_uip_correctness() {
-    test_random::<DecideFirstVariable, CutSecondUIP>()
+    test_configured::<usize, KthUIPFunction>(|c| c.with_weight_heuristic(KthUIPFunction::new(2, 1)))
 }
 
 #[test]
 fn third_uip_correctness() {
-    test_random::<DecideFirstVariable, CutThirdUIP>()
+    test_configured::<usize, KthUIPFunction>(|c| c.with_weight_heuristic(KthUIPFunction::new(3, 1)))
 }
 
 #[test]
@@ -85,10 +127,583 @@ fn all_uip_correctness() {
 
 #[test]
 fn sat_all_uip_correctness() {
-    test_random::<DecideFirstVariable, CutSatAllUIP>()
+    test_configured::<usize, KthUIPFunction>(|c| {
+        c.with_weight_heuristic(KthUIPFunction::new(KthUIPFunction::ALL, 1))
+    })
+}
+
+#[test]
+fn kth_uip_decay_correctness() {
+    test_configured::<usize, KthUIPFunction>(|c| c.with_weight_heuristic(KthUIPFunction::new(4, 2)))
+}
+
+#[test]
+fn lbd_correctness() {
+    test_random::<DecideFirstVariable, CutLBD>()
+}
+
+#[test]
+fn activity_correctness() {
+    test_random::<DecideFirstVariable, CutActivity>()
+}
+
+#[test]
+fn real_activity_correctness() {
+    test_random::<DecideFirstVariable, CutRealActivity>();
+    test_random::<DecideFirstVariable, MinCutConflict<BigRational, ActivityFunction>>();
 }
 
 #[test]
 fn rel_sat_correctness() {
     test_random::<DecideFirstVariable, CutRelSat>()
 }
+
+fn test_configured<W: MinCutWeight, T: WeightHeuristic<W>>(
+    configure: impl Fn(MinCutConflict<W, T>) -> MinCutConflict<W, T>,
+) {
+    test_random_with(|n, formula| {
+        let d = DecideFirstVariable::from_formula(n, formula);
+        let c = configure(MinCutConflict::<W, T>::from_formula(n, formula));
+
+        let mut cdcl = CDCL::new(n, formula, d, c);
+
+        cdcl.solve().then(|| cdcl.get_assignment())
+    })
+}
+
+#[test]
+fn extra_clauses_correctness() {
+    test_configured::<usize, MinCutFunction>(|c| c.with_extra_clauses(true));
+    test_configured::<usize, KthUIPFunction>(|c| {
+        c.with_weight_heuristic(KthUIPFunction::new(2, 1))
+            .with_extra_clauses(true)
+    });
+    test_configured::<BigUint, AllUIPFunction>(|c| c.with_extra_clauses(true));
+}
+
+/// First UIP, also learning `unit` as an extra clause at the first conflict.
+struct ExtraUnit {
+    first_uip: FirstUIP,
+    unit: Option<Clause>,
+}
+
+impl ConflictAnalysis for ExtraUnit {
+    fn from_formula(n: usize, formula: &Formula) -> Self {
+        Self {
+            first_uip: FirstUIP::from_formula(n, formula),
+            unit: None,
+        }
+    }
+
+    fn analyze_conflict(&mut self, formula: &Formula, trail: &Trail, conflict: Clause) -> Clause {
+        self.first_uip.analyze_conflict(formula, trail, conflict)
+    }
+
+    fn analyze_conflict_with_extras(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        conflict: Clause,
+    ) -> (Clause, Vec<Clause>) {
+        let clause = self.analyze_conflict(formula, trail, conflict);
+
+        (clause, self.unit.take().into_iter().collect())
+    }
+
+    fn backtrack_and_add_clause(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        level: usize,
+        clause_id: usize,
+    ) {
+        self.first_uip
+            .backtrack_and_add_clause(formula, trail, level, clause_id);
+    }
+
+    fn propagate_literal(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        literal: Literal,
+        reason_id: usize,
+    ) {
+        self.first_uip
+            .propagate_literal(formula, trail, literal, reason_id);
+    }
+
+    fn decide_literal(&mut self, formula: &Formula, trail: &Trail, literal: Literal) {
+        self.first_uip.decide_literal(formula, trail, literal);
+    }
+}
+
+#[test]
+fn extra_unit_clauses_are_kept() {
+    let n = 4;
+    // x0 is implied, deciding !x3 first is a conflict learning x3
+    let formula: Formula = vec![vec![0, 1], vec![0, !1], vec![3, 2], vec![3, !2]];
+
+    let mut learned = formula.clone();
+    let d = DecideLastVariable::from_formula(n, &formula);
+    let mut c = ExtraUnit::from_formula(n, &formula);
+    c.unit = Some(vec![0]);
+
+    let mut cdcl = CDCL::new(n, &mut learned, d, c);
+
+    assert!(cdcl.solve());
+
+    // propagated once back at level zero, before x1 is decided
+    assert_eq!(cdcl.trail().assignment[0], VariableState::True(0));
+    assert!(cdcl.formula()[formula.len()..].contains(&vec![0]));
+}
+
+#[test]
+fn source_cut_correctness() {
+    test_configured::<usize, MinCutFunction>(|c| c.with_cut_side(CutSide::Source));
+    test_configured::<usize, RelSatFunction>(|c| c.with_cut_side(CutSide::Source));
+    test_configured::<BigUint, AllUIPFunction>(|c| {
+        c.with_cut_side(CutSide::Source).with_extra_clauses(true)
+    });
+}
+
+#[test]
+fn paranoid_correctness() {
+    test_configured::<usize, MinCutFunction>(|c| c.with_paranoid(true));
+    test_configured::<usize, LBDFunction>(|c| c.with_paranoid(true).with_extra_clauses(true));
+}
+
+fn test_with_options<C: ConflictAnalysis>(options: SolverOptions) {
+    test_random_with(|n, formula| cdcl_solve_with::<DecideFirstVariable, C>(n, formula, options))
+}
+
+#[test]
+fn chronological_backtracking_correctness() {
+    let chronological = |threshold| SolverOptions {
+        chronological_backtracking: Some(threshold),
+        ..SolverOptions::default()
+    };
+
+    test_with_options::<FirstUIP>(chronological(0));
+    test_with_options::<CutMinimal>(chronological(0));
+    test_with_options::<CutAllUIP>(chronological(1));
+    test_with_options::<AllUIP>(chronological(0));
+    test_with_options::<HybridMinCut>(chronological(2));
+
+    let options = chronological(0);
+
+    test_random_with(|n, formula| {
+        let d = DecideFirstVariable::from_formula(n, formula);
+        let c = CutMinimal::from_formula(n, formula)
+            .with_extra_clauses(true)
+            .with_paranoid(true);
+
+        let mut cdcl = CDCL::new(n, formula, d, c).with_options(options);
+
+        cdcl.solve().then(|| cdcl.get_assignment())
+    })
+}
+
+/// Decides the last unassigned variable, with no order to reuse the trail by.
+struct DecideLastVariable;
+
+impl DecisionHeuristic for DecideLastVariable {
+    fn from_formula(_n: usize, _formula: &Formula) -> Self {
+        Self
+    }
+
+    fn backtrack_and_add_clause(
+        &mut self,
+        _formula: &Formula,
+        _trail: &Trail,
+        _level: usize,
+        _clause_id: usize,
+    ) {
+    }
+
+    fn propagate_literal(
+        &mut self,
+        _formula: &Formula,
+        _trail: &Trail,
+        _literal: Literal,
+        _reason_id: usize,
+    ) {
+    }
+
+    fn decide_literal(&mut self, _formula: &Formula, trail: &Trail) -> Option<Literal> {
+        (0..trail.assignment.len())
+            .rev()
+            .find(|&variable| trail.assignment[variable].is_unset())
+            .map(|variable| !(variable as Literal))
+    }
+}
+
+#[test]
+fn restarts_correctness() {
+    let restarts = |interval| SolverOptions {
+        restart_interval: Some(interval),
+        ..SolverOptions::default()
+    };
+
+    test_with_options::<FirstUIP>(restarts(1));
+    test_with_options::<CutMinimal>(restarts(1));
+    test_with_options::<CutAllUIP>(restarts(3));
+    test_with_options::<HybridMinCut>(restarts(2));
+    test_with_options::<CutMinimal>(SolverOptions {
+        chronological_backtracking: Some(0),
+        restart_interval: Some(1),
+        ..SolverOptions::default()
+    });
+
+    test_random_with(|n, formula| {
+        cdcl_solve_with::<DecideLastVariable, FirstUIP>(n, formula, restarts(1))
+    });
+}
+
+#[test]
+fn restarts_reuse_the_trail() {
+    let options = SolverOptions {
+        restart_interval: Some(1),
+        ..SolverOptions::default()
+    };
+
+    let mut rng = thread_rng();
+    let n = 30;
+    let (mut restarts, mut reused) = (0, 0);
+
+    for _ in 0..100 {
+        let bnd = n as Literal;
+        let formula: Formula = (0..130)
+            .map(|_| (0..3).map(|_| rng.gen_range(-bnd..bnd)).collect())
+            .collect();
+
+        let mut learned = formula.clone();
+        let d = DecideFirstVariable::from_formula(n, &formula);
+        let c = FirstUIP::from_formula(n, &formula);
+        let mut cdcl = CDCL::new(n, &mut learned, d, c).with_options(options);
+
+        cdcl.solve();
+
+        let statistics = *cdcl.statistics();
+
+        assert!(statistics.restarts <= statistics.conflicts);
+        restarts += statistics.restarts;
+        reused += statistics.reused_levels;
+
+        // without a decision order no level is kept
+        let mut learned = formula.clone();
+        let d = DecideLastVariable::from_formula(n, &formula);
+        let c = FirstUIP::from_formula(n, &formula);
+        let mut cdcl = CDCL::new(n, &mut learned, d, c).with_options(options);
+
+        cdcl.solve();
+
+        assert_eq!(cdcl.statistics().reused_levels, 0);
+    }
+
+    assert!(restarts > 0 && reused > 0);
+}
+
+/// Decides in a priority order, positive first, and moves variable 2 before variable 1 once a
+/// clause is learned.
+struct PromoteAfterConflict {
+    order: Vec<Variable>,
+    backtracks: Vec<usize>,
+}
+
+impl DecisionHeuristic for PromoteAfterConflict {
+    fn from_formula(n: usize, _formula: &Formula) -> Self {
+        Self {
+            order: (0..n).collect(),
+            backtracks: vec![],
+        }
+    }
+
+    fn backtrack_and_add_clause(
+        &mut self,
+        _formula: &Formula,
+        _trail: &Trail,
+        _level: usize,
+        _clause_id: usize,
+    ) {
+        self.order.retain(|&variable| variable != 2);
+        self.order.insert(1, 2);
+    }
+
+    fn backtrack(&mut self, _formula: &Formula, _trail: &Trail, level: usize) {
+        self.backtracks.push(level);
+    }
+
+    fn propagate_literal(
+        &mut self,
+        _formula: &Formula,
+        _trail: &Trail,
+        _literal: Literal,
+        _reason_id: usize,
+    ) {
+    }
+
+    fn decide_literal(&mut self, _formula: &Formula, trail: &Trail) -> Option<Literal> {
+        self.order
+            .iter()
+            .find(|&&variable| trail.assignment[variable].is_unset())
+            .map(|&variable| variable as Literal)
+    }
+
+    fn decides_before(&self, a: Variable, b: Variable) -> bool {
+        let rank = |variable| self.order.iter().position(|&v| v == variable);
+
+        rank(a) < rank(b)
+    }
+}
+
+#[test]
+fn restarts_keep_the_agreeing_prefix() {
+    let options = SolverOptions {
+        restart_interval: Some(1),
+        ..SolverOptions::default()
+    };
+
+    // deciding 0, 1, 2 and 3 fails, the learned clause !1 | !3 backjumps to level 2
+    let n = 6;
+    let formula: Formula = vec![vec![!1, !3, 4], vec![!1, !3, !4]];
+
+    let mut learned = formula.clone();
+    let d = PromoteAfterConflict::from_formula(n, &formula);
+    let c = FirstUIP::from_formula(n, &formula);
+    let mut cdcl = CDCL::new(n, &mut learned, d, c).with_options(options);
+
+    assert!(cdcl.solve());
+
+    // 2 now comes before the decision 1 of level 2, only level 1 is kept
+    assert_eq!(cdcl.decision_heuristic().backtracks, vec![1]);
+    assert_eq!(cdcl.statistics().restarts, 1);
+    assert_eq!(cdcl.statistics().reused_levels, 1);
+
+    let decisions: Vec<_> = cdcl.trail().levels[1..]
+        .iter()
+        .map(|level| level[0].0)
+        .collect();
+
+    assert_eq!(decisions, vec![0, 2, 1, 4, 5]);
+    assert!(is_satisfying(&formula, &cdcl.get_assignment()));
+}
+
+#[test]
+fn simplification_correctness() {
+    let simplify = |restart_interval| SolverOptions {
+        restart_interval,
+        simplify: true,
+        ..SolverOptions::default()
+    };
+
+    test_with_options::<FirstUIP>(simplify(None));
+    test_with_options::<CutMinimal>(simplify(None));
+    test_with_options::<CutAllUIP>(simplify(Some(2)));
+    test_with_options::<AllUIP>(simplify(None));
+    test_with_options::<HybridMinCut>(simplify(Some(1)));
+    test_with_options::<CutMinimal>(SolverOptions {
+        chronological_backtracking: Some(0),
+        ..simplify(Some(1))
+    });
+
+    // every restart goes back to level zero
+    test_random_with(|n, formula| {
+        let d = DecideLastVariable::from_formula(n, formula);
+        let c = CutMinimal::from_formula(n, formula)
+            .with_extra_clauses(true)
+            .with_paranoid(true);
+
+        let mut cdcl = CDCL::new(n, formula, d, c).with_options(simplify(Some(1)));
+
+        cdcl.solve().then(|| cdcl.get_assignment())
+    });
+}
+
+/// Checks after every simplification that the clauses still watched hold no literal false at
+/// level zero, except those propagated by a later clause of the same simplification.
+struct CheckSimplification<C: ConflictAnalysis> {
+    analysis: C,
+    reasons: Vec<usize>,
+    checked_literals: usize,
+}
+
+impl<C: ConflictAnalysis> ConflictAnalysis for CheckSimplification<C> {
+    fn from_formula(n: usize, formula: &Formula) -> Self {
+        Self {
+            analysis: C::from_formula(n, formula),
+            reasons: vec![0; n],
+            checked_literals: 0,
+        }
+    }
+
+    fn analyze_conflict(&mut self, formula: &Formula, trail: &Trail, conflict: Clause) -> Clause {
+        self.analysis.analyze_conflict(formula, trail, conflict)
+    }
+
+    fn backtrack_and_add_clause(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        level: usize,
+        clause_id: usize,
+    ) {
+        self.analysis
+            .backtrack_and_add_clause(formula, trail, level, clause_id);
+    }
+
+    fn backtrack(&mut self, formula: &Formula, trail: &Trail, level: usize) {
+        self.analysis.backtrack(formula, trail, level);
+    }
+
+    fn simplify(&mut self, formula: &Formula, trail: &Trail) {
+        for (clause_id, clause) in formula.iter().enumerate() {
+            if trail.clause_types[clause_id] == ClauseType::Satisfied {
+                continue;
+            }
+
+            for &literal in clause {
+                let variable = variable_name(literal);
+
+                if !trail.assignment[variable].is_unset() {
+                    let reason_id = self.reasons[variable];
+
+                    assert!(reason_id > clause_id);
+                    assert_eq!(trail.clause_types[reason_id], ClauseType::Unit(!literal));
+                }
+
+                self.checked_literals += 1;
+            }
+        }
+
+        self.analysis.simplify(formula, trail);
+    }
+
+    fn propagate_literal(
+        &mut self,
+        formula: &Formula,
+        trail: &Trail,
+        literal: Literal,
+        reason_id: usize,
+    ) {
+        self.reasons[variable_name(literal)] = reason_id;
+        self.analysis
+            .propagate_literal(formula, trail, literal, reason_id);
+    }
+
+    fn decide_literal(&mut self, formula: &Formula, trail: &Trail, literal: Literal) {
+        self.analysis.decide_literal(formula, trail, literal);
+    }
+}
+
+#[test]
+fn simplification_removes_level_zero() {
+    let options = SolverOptions {
+        restart_interval: Some(1),
+        simplify: true,
+        ..SolverOptions::default()
+    };
+
+    let mut rng = thread_rng();
+    let n = 30;
+    let mut simplifications = 0;
+    let mut removed_literals = 0;
+    let mut checked_literals = 0;
+
+    for _ in 0..100 {
+        let bnd = n as Literal;
+        let formula: Formula = (0..130)
+            .map(|_| (0..3).map(|_| rng.gen_range(-bnd..bnd)).collect())
+            .collect();
+
+        let mut learned = formula.clone();
+        let d = DecideLastVariable::from_formula(n, &formula);
+        let c = CheckSimplification::<CutMinimal>::from_formula(n, &formula);
+        let mut cdcl = CDCL::new(n, &mut learned, d, c).with_options(options);
+
+        let answer = cdcl.solve().then(|| cdcl.get_assignment());
+        let statistics = *cdcl.statistics();
+
+        simplifications += statistics.simplifications;
+        removed_literals += statistics.removed_literals;
+        checked_literals += cdcl.conflict_analysis().checked_literals;
+
+        let satisfied = cdcl
+            .trail()
+            .clause_types
+            .iter()
+            .filter(|&&clause_type| clause_type == ClauseType::Satisfied)
+            .count();
+
+        // clause ids are kept, satisfied clauses stay in place unwatched
+        assert!(learned.len() >= formula.len());
+        assert!(statistics.unwatched_clauses <= satisfied);
+        assert!(learned.iter().all(|clause| !clause.is_empty()));
+
+        for (original, simplified) in formula.iter().zip(&learned) {
+            assert!(simplified.iter().all(|literal| original.contains(literal)));
+        }
+
+        if let Some(assignment) = answer {
+            assert!(is_satisfying(&formula, &assignment));
+            assert!(is_satisfying(&learned, &assignment));
+        }
+    }
+
+    assert!(simplifications > 0);
+    assert!(removed_literals > 0);
+    assert!(checked_literals > 0);
+}
+
+#[test]
+fn hybrid_correctness() {
+    test_random::<DecideFirstVariable, HybridMinCut>()
+}
+
+#[test]
+fn hybrid_statistics_are_reported() {
+    use ail_project::cdcl::propagation::DynConflictAnalysis;
+
+    let mut rng = thread_rng();
+    let n = 30;
+    let bnd = n as Literal;
+    let formula: Formula = (0..130)
+        .map(|_| (0..3).map(|_| rng.gen_range(-bnd..bnd)).collect())
+        .collect();
+
+    let mut learned = formula.clone();
+    let d = DecideFirstVariable::from_formula(n, &formula);
+    let c: Box<dyn DynConflictAnalysis> = Box::new(HybridMinCut::from_formula(n, &formula));
+    let mut cdcl = CDCL::new(n, &mut learned, d, c);
+
+    let satisfiable = cdcl.solve();
+
+    // behind the trait object, as in the CLI
+    let statistics = cdcl.conflict_analysis().named_statistics();
+    let count = |name| statistics.iter().find(|(key, _)| *key == name).unwrap().1;
+
+    // the last conflict of an unsatisfiable formula is at level zero and not analyzed
+    assert_eq!(
+        count("sampled conflicts") + !satisfiable as usize,
+        cdcl.statistics().conflicts
+    );
+    assert_eq!(
+        count("primary wins") + count("secondary wins") + count("ties"),
+        count("sampled conflicts")
+    );
+}
+
+#[test]
+fn dynamic_correctness() {
+    let analyses = [
+        conflict_analysis_factory::<FirstUIP>(),
+        conflict_analysis_factory::<CutMinimal>(),
+        conflict_analysis_factory::<CutAllUIP>(),
+    ];
+    let decision = decision_heuristic_factory::<DecideFirstVariable>();
+
+    for analysis in &analyses {
+        test_random_with(|n, formula| {
+            cdcl_solve_dyn(n, formula, decision.as_ref(), analysis.as_ref())
+        })
+    }
+}
@@ -0,0 +1,87 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use ail_project::cdcl::decision::DecideFirstVariable;
+use ail_project::cdcl::dynamic::{conflict_analysis_factory, decision_heuristic_factory};
+use ail_project::cdcl::first_uip::FirstUIP;
+use ail_project::cdcl::mincut::{CutAllUIP, CutFirstUIP, CutLBD, CutMinimal};
+use ail_project::cdcl::propagation::{ConflictAnalysis, DynConflictAnalysis};
+use ail_project::cdcl::trace::{ConflictTrace, TraceConflicts};
+use ail_project::cdcl::*;
+use rand::{thread_rng, Rng};
+
+/// Schemes traced next to the active one.
+fn schemes(n: usize, formula: &Formula) -> Vec<(String, Box<dyn DynConflictAnalysis>)> {
+    fn scheme(
+        name: &str,
+        analysis: impl ConflictAnalysis + 'static,
+    ) -> (String, Box<dyn DynConflictAnalysis>) {
+        (name.to_string(), Box::new(analysis))
+    }
+
+    vec![
+        scheme("first-uip-basic", FirstUIP::new(n)),
+        scheme("first-uip", CutFirstUIP::from_formula(n, formula)),
+        scheme("all-uip", CutAllUIP::from_formula(n, formula)),
+        scheme("min-cut", CutMinimal::from_formula(n, formula)),
+        scheme("lbd", CutLBD::from_formula(n, formula)),
+    ]
+}
+
+#[test]
+fn traced_schemes_share_the_search() {
+    let n = 30;
+    let mut rng = thread_rng();
+    let decision = decision_heuristic_factory::<DecideFirstVariable>();
+    let first_uip = conflict_analysis_factory::<FirstUIP>();
+
+    for _ in 0..100 {
+        let bnd = n as Literal;
+        let formula: Formula = (0..130)
+            .map(|_| (0..3).map(|_| rng.gen_range(-bnd..bnd)).collect())
+            .collect();
+
+        let traces = Rc::new(RefCell::new(vec![]));
+
+        let traced = |n: usize, formula: &Formula| -> Box<dyn DynConflictAnalysis> {
+            let traces = traces.clone();
+
+            Box::new(TraceConflicts::new(
+                "first-uip-basic",
+                first_uip.create(n, formula),
+                schemes(n, formula),
+                move |trace: &ConflictTrace| traces.borrow_mut().push(trace.clone()),
+            ))
+        };
+
+        let mut plain = formula.clone();
+        let mut with_trace = formula.clone();
+
+        let expected = cdcl_solve_dyn(n, &mut plain, decision.as_ref(), first_uip.as_ref());
+        let answer = cdcl_solve_dyn(n, &mut with_trace, decision.as_ref(), &traced);
+
+        // tracing does not change the search
+        assert_eq!(answer, expected);
+        assert_eq!(with_trace, plain);
+
+        let traces = traces.take();
+
+        assert_eq!(traces.len(), plain.len() - formula.len());
+
+        for (index, trace) in traces.iter().enumerate() {
+            assert_eq!(trace.conflict, index + 1);
+            assert_eq!(trace.clauses.len(), schemes(n, &formula).len() + 1);
+            assert_eq!(trace.clauses[0].scheme, "first-uip-basic");
+            assert_eq!(trace.clauses[1].scheme, "first-uip-basic");
+            assert_eq!(trace.clauses[0].clause, trace.clauses[1].clause);
+            assert_eq!(trace.clauses[0].clause, plain[formula.len() + index]);
+
+            for traced in &trace.clauses {
+                assert!(traced.lbd <= traced.clause.len());
+                assert!(traced.backjump_level < trace.level);
+            }
+
+            assert_eq!(trace.to_lines().lines().count(), trace.clauses.len());
+        }
+    }
+}